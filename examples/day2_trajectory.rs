@@ -0,0 +1,39 @@
+//! Exports the trajectory of the day 2 submarine for both parts of the puzzle.
+//!
+//! usage: cargo run --example day2_trajectory -- csv|svg [PATH]
+//!
+//! PATH defaults to `inputs/day2.txt`, the result is written to the standard output.
+use aoc_2021::day2::{trace, trajectory_csv, trajectory_svg, Aim, Day2, Plain};
+use aoc_2021::Solution;
+use std::env;
+use std::fs;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let path = args.get(1).map_or("inputs/day2.txt", String::as_str);
+
+    let movements = match fs::read_to_string(path) {
+        Ok(s) => Day2::parse(&s).unwrap_or_else(|err| {
+            eprintln!("error: {}", err.in_day(2));
+            process::exit(1);
+        }),
+        Err(err) => {
+            eprintln!("could not read '{}': {}", path, err);
+            process::exit(1);
+        }
+    };
+    let traces = [
+        ("plain", trace(&movements, &Plain).collect()),
+        ("aim", trace(&movements, &Aim).collect()),
+    ];
+
+    match args.first().map(String::as_str) {
+        Some("csv") => print!("{}", trajectory_csv(&traces)),
+        Some("svg") => print!("{}", trajectory_svg(&traces)),
+        _ => {
+            eprintln!("usage: day2_trajectory csv|svg [PATH]");
+            process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Exports the coverage of the day 5 vents as a plain PGM heatmap.
+//!
+//! usage: cargo run --example day5_heatmap -- [--all] [PATH]
+//!
+//! Only horizontal and vertical segments are drawn unless `--all` is given. PATH defaults
+//! to `inputs/day5.txt`, the image is written to the standard output and downsampled to
+//! at most 2048 pixels on a side.
+use aoc_2021::day5::{coverage, heatmap_pgm, Day5, HEATMAP_SIDE};
+use aoc_2021::Solution;
+use std::env;
+use std::fs;
+use std::process;
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let count_diagonals = args.first().is_some_and(|arg| arg == "--all");
+    if count_diagonals {
+        args.remove(0);
+    }
+    let path = args.first().map_or("inputs/day5.txt", String::as_str);
+
+    let segments = match fs::read_to_string(path) {
+        Ok(s) => Day5::parse(&s).unwrap_or_else(|err| {
+            eprintln!("error: {}", err.in_day(5));
+            process::exit(1);
+        }),
+        Err(err) => {
+            eprintln!("could not read '{}': {}", path, err);
+            process::exit(1);
+        }
+    };
+
+    print!(
+        "{}",
+        heatmap_pgm(&coverage(&segments, count_diagonals), HEATMAP_SIDE)
+    );
+}
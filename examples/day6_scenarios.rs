@@ -0,0 +1,59 @@
+//! Exports the day 6 population on each day under a few lanternfish life cycles.
+//!
+//! usage: cargo run --example day6_scenarios -- [DAYS] [PATH]
+//!
+//! DAYS defaults to 80 and PATH to `inputs/day6.txt`, the CSV is written to the standard
+//! output.
+use aoc_2021::day6::{series_csv, Day6, Lifecycle};
+use aoc_2021::Solution;
+use num_bigint::BigUint;
+use std::env;
+use std::fs;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let days = match args.first().map(|arg| arg.parse::<usize>()) {
+        None => 80,
+        Some(Ok(days)) => days,
+        Some(Err(_)) => {
+            eprintln!("usage: day6_scenarios [DAYS] [PATH]");
+            process::exit(2);
+        }
+    };
+    let path = args.get(1).map_or("inputs/day6.txt", String::as_str);
+
+    let population = match fs::read_to_string(path) {
+        Ok(s) => Day6::parse(&s).unwrap_or_else(|err| {
+            eprintln!("error: {}", err.in_day(6));
+            process::exit(1);
+        }),
+        Err(err) => {
+            eprintln!("could not read '{}': {}", path, err);
+            process::exit(1);
+        }
+    };
+    let puzzle = Lifecycle::default();
+    let scenarios: Vec<(&str, Vec<BigUint>)> = vec![
+        ("puzzle", puzzle.series(&population, days)),
+        (
+            "fast",
+            Lifecycle {
+                reset: 4,
+                newborn: 6,
+                ..puzzle
+            }
+            .series(&population, days),
+        ),
+        (
+            "mortal",
+            Lifecycle {
+                lifespan: Some(30),
+                ..puzzle
+            }
+            .series(&population, days),
+        ),
+    ];
+
+    print!("{}", series_csv(&scenarios));
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use crate::error::ParseError;
+
+/// Expected answers for one set of puzzle inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnswerSet {
+    pub name: String,
+    /// Directory with the `dayN.txt` inputs of the set.
+    pub dir: String,
+    answers: HashMap<(u8, u8), String>,
+}
+
+impl AnswerSet {
+    fn new(name: &str) -> AnswerSet {
+        AnswerSet {
+            name: String::from(name),
+            dir: String::from(name),
+            answers: HashMap::new(),
+        }
+    }
+
+    /// Expected answer to the given part of the day, `None` if it is not known.
+    pub fn expected(&self, day: u8, part: u8) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+}
+
+/// Parses a basic string starting at the beginning of `s`, returns it together with
+/// the rest of `s` after the closing quote.
+fn parse_string<'a>(text: &str, s: &'a str) -> Result<(String, &'a str), ParseError> {
+    let mut chars = s
+        .strip_prefix('"')
+        .ok_or_else(|| ParseError::at(text, s, "'\"'"))?
+        .char_indices();
+    let mut value = String::new();
+
+    while let Some((k, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &s[k + 2..])),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, c @ ('"' | '\\'))) => value.push(c),
+                _ => return Err(ParseError::at(text, &s[k + 1..], "'\\n', '\\\"' or '\\\\'")),
+            },
+            c => value.push(c),
+        }
+    }
+
+    // the string continues up to the end of the line, which is also the end of `s`
+    Err(ParseError::at(text, &s[s.len()..], "'\"'"))
+}
+
+/// Parses the answers file, a subset of TOML with a table for each input set:
+///
+/// ```toml
+/// [examples]
+/// dir = "tests/examples"
+/// day1 = ["7", "5"]
+/// ```
+///
+/// `dir` defaults to the name of the set. Answers may be omitted or left empty when unknown.
+pub fn parse_answers(text: &str) -> Result<Vec<AnswerSet>, ParseError> {
+    let mut sets: Vec<AnswerSet> = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            sets.push(AnswerSet::new(name.trim()));
+            continue;
+        }
+
+        let set = sets
+            .last_mut()
+            .ok_or_else(|| ParseError::at(text, line, "'[name]' of an input set"))?;
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| ParseError::at(text, line, "'key = value'"))?;
+        let (key, value) = (key.trim(), value.trim());
+
+        if key == "dir" {
+            let (dir, rest) = parse_string(text, value)?;
+            if !rest.trim().is_empty() {
+                return Err(ParseError::at(text, rest.trim(), "end of line"));
+            }
+            set.dir = dir;
+            continue;
+        }
+
+        let day = key
+            .strip_prefix("day")
+            .and_then(|day| day.parse::<u8>().ok())
+            .filter(|day| (1..=25).contains(day))
+            .ok_or_else(|| ParseError::at(text, key, "'dir' or 'dayN' with N from 1 to 25"))?;
+
+        let mut rest = value
+            .strip_prefix('[')
+            .ok_or_else(|| ParseError::at(text, value, "'['"))?
+            .trim_start();
+        for part in 1..=2 {
+            if rest.starts_with(']') {
+                break;
+            }
+            let (answer, after) = parse_string(text, rest)?;
+            if !answer.is_empty() {
+                set.answers.insert((day, part), answer);
+            }
+            rest = after.trim_start();
+            rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+        }
+        if rest != "]" {
+            return Err(ParseError::at(text, rest, "']'"));
+        }
+    }
+
+    Ok(sets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_answers;
+    #[test]
+    fn test_answers_file() {
+        let sets = parse_answers(
+            "# comment\n[inputs]\nday1 = [\"1292\", \"1262\"]\nday25 = [\"568\"]\n\n\
+             [examples]\ndir = \"tests/examples\"\nday13 = [\"17\", \"#.\\n.#\\n\"]\nday14 = [\"\", \"2\"]\n",
+        )
+        .unwrap();
+
+        assert_eq!(sets.len(), 2);
+        assert_eq!(
+            (sets[0].name.as_str(), sets[0].dir.as_str()),
+            ("inputs", "inputs")
+        );
+        assert_eq!(sets[0].expected(1, 2), Some("1262"));
+        assert_eq!(sets[0].expected(25, 2), None);
+        assert_eq!(sets[1].dir, "tests/examples");
+        assert_eq!(sets[1].expected(13, 2), Some("#.\n.#\n"));
+        assert_eq!(sets[1].expected(14, 1), None);
+
+        let err = parse_answers("[inputs]\nday26 = [\"1\"]\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert!(err
+            .to_string()
+            .starts_with("line 2, column 1: expected 'dir' or 'dayN'"));
+        assert!(parse_answers("day1 = [\"1\"]").is_err());
+        assert!(parse_answers("[inputs]\nday1 = [\"1\", \"2\", \"3\"]").is_err());
+        assert!(parse_answers("[inputs]\nday1 = [\"1").is_err());
+    }
+}
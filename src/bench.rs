@@ -0,0 +1,166 @@
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::error::ParseError;
+use crate::solution::Solver;
+
+pub const TABLE_HEADER: &str = "Day  Step           Min      Median         Max";
+
+/// Minimum, median and maximum duration of a repeatedly measured step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Timing {
+    fn from(mut samples: Vec<Duration>) -> Timing {
+        samples.sort_unstable();
+
+        Timing {
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            max: samples[samples.len() - 1],
+        }
+    }
+
+    fn json(&self) -> String {
+        format!(
+            "{{\"min_ns\": {}, \"median_ns\": {}, \"max_ns\": {}}}",
+            self.min.as_nanos(),
+            self.median.as_nanos(),
+            self.max.as_nanos()
+        )
+    }
+}
+
+/// Timings of the parse step and of both parts of a single day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayTimings {
+    pub day: u8,
+    pub parse: Timing,
+    pub part1: Timing,
+    pub part2: Timing,
+}
+
+impl DayTimings {
+    fn steps(&self) -> [(&str, &Timing); 3] {
+        [
+            ("parse", &self.parse),
+            ("part1", &self.part1),
+            ("part2", &self.part2),
+        ]
+    }
+}
+
+/// Rows of the timing table, one for each step of the day.
+impl fmt::Display for DayTimings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (k, (step, timing)) in self.steps().iter().enumerate() {
+            if k > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{:>3}  {:<6} {:>11.1?} {:>11.1?} {:>11.1?}",
+                self.day, step, timing.min, timing.median, timing.max
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs `f` the given number of times and collects the durations of the runs.
+fn measure<T>(iterations: usize, mut f: impl FnMut() -> T) -> Timing {
+    let samples = (0..iterations)
+        .map(|_| {
+            let start = Instant::now();
+            let result = f();
+            let elapsed = start.elapsed();
+
+            // keep the result alive so that the computation is not optimized out,
+            // but do not count its deallocation
+            drop(black_box(result));
+            elapsed
+        })
+        .collect();
+
+    Timing::from(samples)
+}
+
+/// Times parsing of the input and both parts of the solution, each `iterations` times.
+pub fn bench_day(
+    solver: &dyn Solver,
+    input: &str,
+    iterations: usize,
+) -> Result<DayTimings, ParseError> {
+    let parsed = solver.parse_input(input)?;
+
+    Ok(DayTimings {
+        day: solver.day(),
+        parse: measure(iterations, || solver.parse_input(input)),
+        part1: measure(iterations, || solver.solve_part(&*parsed, 1)),
+        part2: measure(iterations, || solver.solve_part(&*parsed, 2)),
+    })
+}
+
+/// Machine readable representation of the timings, durations are in nanoseconds.
+pub fn json(iterations: usize, timings: &[DayTimings]) -> String {
+    let days = timings
+        .iter()
+        .map(|t| {
+            let steps = t
+                .steps()
+                .iter()
+                .map(|(step, timing)| format!("\"{}\": {}", step, timing.json()))
+                .collect::<Vec<String>>()
+                .join(", ");
+            format!("    {{\"day\": {}, {}}}", t.day, steps)
+        })
+        .collect::<Vec<String>>()
+        .join(",\n");
+
+    format!(
+        "{{\n  \"iterations\": {},\n  \"days\": [\n{}\n  ]\n}}",
+        iterations, days
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{json, DayTimings, Timing};
+    use std::time::Duration;
+    #[test]
+    fn test_timings_report() {
+        let timing = Timing::from([5, 1, 4, 2, 3].map(Duration::from_micros).to_vec());
+        assert_eq!(
+            (timing.min, timing.median, timing.max),
+            (
+                Duration::from_micros(1),
+                Duration::from_micros(3),
+                Duration::from_micros(5)
+            )
+        );
+
+        let timings = DayTimings {
+            day: 7,
+            parse: timing,
+            part1: timing,
+            part2: Timing::from(vec![Duration::from_millis(2)]),
+        };
+        assert_eq!(
+            timings.to_string().lines().last().unwrap(),
+            "  7  part2        2.0ms       2.0ms       2.0ms"
+        );
+        assert_eq!(
+            json(5, &[timings]),
+            "{\n  \"iterations\": 5,\n  \"days\": [\n    {\"day\": 7, \
+             \"parse\": {\"min_ns\": 1000, \"median_ns\": 3000, \"max_ns\": 5000}, \
+             \"part1\": {\"min_ns\": 1000, \"median_ns\": 3000, \"max_ns\": 5000}, \
+             \"part2\": {\"min_ns\": 2000000, \"median_ns\": 2000000, \"max_ns\": 2000000}}\n  ]\n}"
+        );
+    }
+}
@@ -1,45 +1,300 @@
-use std::convert::identity;
-use std::fs;
-
-fn triad_sums(vec: Vec<u32>) -> Vec<u32> {
-    vec.iter()
-        .zip(vec.iter().skip(1))
-        .zip(vec.iter().skip(2))
-        .map(|((a, b), c)| a + b + c)
-        .collect()
-}
-
-fn general_solution<F: Fn(Vec<u32>) -> Vec<u32>>(f: F) -> String {
-    // load file into a vector of numbers
-    let input: String = fs::read_to_string("inputs/day1.txt").expect("Could not read file");
-    let lines: Vec<u32> = input.lines().map(|s| s.parse::<u32>().unwrap()).collect();
-    // perform pre-processing aggregation
-    let aggregates = f(lines);
-    // count cases
-    aggregates
+use crate::error::{parse_token, ParseError};
+use crate::solution::{Answer, Solution};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+use std::ops::Range;
+
+pub struct Day1;
+
+/// Iterator over the sums of all the sliding windows of a fixed size, see `window_sums`.
+pub struct WindowSums<I> {
+    depths: I,
+    window: VecDeque<u32>,
+    size: usize,
+    sum: u64,
+}
+
+impl<I: Iterator<Item = u32>> Iterator for WindowSums<I> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        loop {
+            let depth = self.depths.next()?;
+            self.window.push_back(depth);
+            self.sum += depth as u64;
+
+            if self.window.len() > self.size {
+                self.sum -= self.window.pop_front().unwrap() as u64;
+            }
+            if self.window.len() == self.size {
+                return Some(self.sum);
+            }
+        }
+    }
+}
+
+/// Sums of all the sliding windows of `size` consecutive measurements. Only the current
+/// window is kept in memory, so `depths` may be an unbounded stream.
+pub fn window_sums<I: IntoIterator<Item = u32>>(depths: I, size: usize) -> WindowSums<I::IntoIter> {
+    assert!(size > 0, "window size has to be positive");
+
+    WindowSums {
+        depths: depths.into_iter(),
+        window: VecDeque::with_capacity(size + 1),
+        size,
+        sum: 0,
+    }
+}
+
+/// Counts of the increases between consecutive sums of sliding windows of several sizes,
+/// fed one depth at a time. Sums of consecutive windows of size `w` differ by the newest
+/// depth minus the one `w` measurements before it, so only the last depths are kept.
+struct Increases<'a> {
+    windows: &'a [usize],
+    recent: VecDeque<u32>,
+    longest: usize,
+    counts: Vec<usize>,
+}
+
+impl<'a> Increases<'a> {
+    fn new(windows: &'a [usize]) -> Increases<'a> {
+        assert!(
+            windows.iter().all(|w| *w > 0),
+            "window size has to be positive"
+        );
+        let longest = windows.iter().copied().max().unwrap_or(0);
+
+        Increases {
+            windows,
+            recent: VecDeque::with_capacity(longest + 1),
+            longest,
+            counts: vec![0; windows.len()],
+        }
+    }
+
+    fn push(&mut self, depth: u32) {
+        for (count, window) in self.counts.iter_mut().zip(self.windows) {
+            if self.recent.len() >= *window && depth > self.recent[self.recent.len() - window] {
+                *count += 1;
+            }
+        }
+        self.recent.push_back(depth);
+        if self.recent.len() > self.longest {
+            self.recent.pop_front();
+        }
+    }
+}
+
+/// Counts the increases between consecutive sums of sliding windows of the given size.
+pub fn general_solution<I: IntoIterator<Item = u32>>(depths: I, window: usize) -> usize {
+    let windows = [window];
+    let mut increases = Increases::new(&windows);
+    for depth in depths {
+        increases.push(depth);
+    }
+
+    increases.counts[0]
+}
+
+/// Window sizes of the first and the second part.
+pub const WINDOWS: [usize; 2] = [1, 3];
+
+/// Same as `general_solution`, but the depths are read line by line from `reader`, such as
+/// the locked standard input, instead of being parsed up front. Malformed lines are reported
+/// as `InvalidData` errors wrapping the `ParseError`.
+pub fn general_solution_from<R: BufRead>(reader: R, window: usize) -> io::Result<usize> {
+    general_solutions_from(reader, &[window]).map(|counts| counts[0])
+}
+
+/// Same as `general_solution_from`, but counts the increases for each of the windows in
+/// a single pass over `reader`.
+pub fn general_solutions_from<R: BufRead>(reader: R, windows: &[usize]) -> io::Result<Vec<usize>> {
+    let mut increases = Increases::new(windows);
+
+    for (k, line) in reader.lines().enumerate() {
+        let line = line?;
+        let depth: u32 = parse_token(&line, &line, "depth measurement").map_err(|err| {
+            io::Error::new(io::ErrorKind::InvalidData, err.offset_lines(k).in_day(1))
+        })?;
+        increases.push(depth);
+    }
+
+    Ok(increases.counts)
+}
+
+/// Statistics of the trends in a series of depths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trends {
+    /// Indices of the longest strictly increasing run of values, the first one on ties.
+    pub longest_increase: Range<usize>,
+    /// Indices of the longest strictly decreasing run of values, the first one on ties.
+    pub longest_decrease: Range<usize>,
+    /// Number of maximal runs of at least two equal values.
+    pub plateaus: usize,
+    /// Indices and values deviating from the rolling mean by more than the given number
+    /// of standard deviations.
+    pub anomalies: Vec<(usize, u64)>,
+}
+
+/// Mean and standard deviation of the values.
+fn mean_and_deviation(values: &VecDeque<u64>) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().map(|v| *v as f64).sum::<f64>() / n;
+    let variance = values
         .iter()
-        .zip(aggregates.iter().skip(1))
-        .filter(|(a, b)| a < b)
-        .count()
-        .to_string()
+        .map(|v| (*v as f64 - mean).powi(2))
+        .sum::<f64>()
+        / n;
+
+    (mean, variance.sqrt())
 }
 
-pub fn ex1() -> String {
-    // count cases using raw values
-    general_solution(identity)
+/// Computes the trends of the sums of sliding windows of the given size in a single pass.
+/// A sum is an anomaly if it deviates from the mean of the `rolling` sums preceding it by
+/// more than `k` of their standard deviations.
+pub fn trends<I: IntoIterator<Item = u32>>(
+    depths: I,
+    window: usize,
+    rolling: usize,
+    k: f64,
+) -> Trends {
+    assert!(rolling > 0, "rolling window size has to be positive");
+
+    let mut trends = Trends {
+        longest_increase: 0..0,
+        longest_decrease: 0..0,
+        plateaus: 0,
+        anomalies: Vec::new(),
+    };
+    // starts of the current increasing and decreasing runs
+    let (mut increase, mut decrease) = (0, 0);
+    let mut on_plateau = false;
+    let mut previous: Option<u64> = None;
+    let mut recent: VecDeque<u64> = VecDeque::with_capacity(rolling + 1);
+
+    for (i, value) in window_sums(depths, window).enumerate() {
+        let ordering = previous.map(|p| value.cmp(&p));
+        match ordering {
+            Some(Ordering::Greater) => decrease = i,
+            Some(Ordering::Less) => increase = i,
+            Some(Ordering::Equal) => {
+                // count the plateau once, with its second value
+                if !on_plateau {
+                    trends.plateaus += 1;
+                }
+                (increase, decrease) = (i, i);
+            }
+            None => {}
+        }
+        on_plateau = ordering == Some(Ordering::Equal);
+
+        if i + 1 - increase > trends.longest_increase.len() {
+            trends.longest_increase = increase..i + 1;
+        }
+        if i + 1 - decrease > trends.longest_decrease.len() {
+            trends.longest_decrease = decrease..i + 1;
+        }
+
+        if recent.len() == rolling {
+            let (mean, deviation) = mean_and_deviation(&recent);
+            if (value as f64 - mean).abs() > k * deviation {
+                trends.anomalies.push((i, value));
+            }
+            recent.pop_front();
+        }
+        recent.push_back(value);
+        previous = Some(value);
+    }
+
+    trends
 }
 
-pub fn ex2() -> String {
-    // count cases after getting a rolling sum of three
-    general_solution(triad_sums)
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+    type Input = Vec<u32>;
+
+    fn parse(input: &str) -> Result<Vec<u32>, ParseError> {
+        // parse lines into a vector of numbers
+        input
+            .lines()
+            .map(|s| parse_token(input, s, "depth measurement"))
+            .collect()
+    }
+
+    fn part1(depths: &Vec<u32>) -> Answer {
+        // count cases using raw values
+        Ok(general_solution(depths.iter().copied(), WINDOWS[0]).to_string())
+    }
+
+    fn part2(depths: &Vec<u32>) -> Answer {
+        // count cases after getting a rolling sum of three
+        Ok(general_solution(depths.iter().copied(), WINDOWS[1]).to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::{general_solution_from, general_solutions_from, trends, window_sums, Day1};
+    use crate::solution::Solution;
+    use std::fs;
+    use std::io::{BufReader, ErrorKind};
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "1292");
-        assert_eq!(ex2(), "1262");
+        let input = Day1::parse(&fs::read_to_string("inputs/day1.txt").unwrap()).unwrap();
+        assert_eq!(Day1::part1(&input).unwrap(), "1292");
+        assert_eq!(Day1::part2(&input).unwrap(), "1262");
+    }
+
+    #[test]
+    fn test_streaming_windows() {
+        assert_eq!(
+            window_sums([1, 2, 3, 4, 5], 2).collect::<Vec<u64>>(),
+            vec![3, 5, 7, 9]
+        );
+        assert_eq!(window_sums([1, 2], 3).count(), 0);
+
+        let file = fs::File::open("inputs/day1.txt").unwrap();
+        assert_eq!(
+            general_solution_from(BufReader::new(file), 3).unwrap(),
+            1262
+        );
+        let file = fs::File::open("inputs/day1.txt").unwrap();
+        assert_eq!(
+            general_solutions_from(BufReader::new(file), &[1, 3]).unwrap(),
+            vec![1292, 1262]
+        );
+        assert_eq!(
+            general_solutions_from("1\n3\n2\n4\n".as_bytes(), &[1, 2, 5]).unwrap(),
+            vec![2, 2, 0]
+        );
+
+        let err = general_solution_from("1\n2\nthree\n".as_bytes(), 1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "day 1, line 3, column 1: expected depth measurement, found 'three'"
+        );
+    }
+
+    #[test]
+    fn test_trends() {
+        let depths = [10, 10, 10, 10, 50, 10, 11, 11, 12, 13];
+        let stats = trends(depths, 1, 3, 2.0);
+        assert_eq!(stats.longest_increase, 7..10);
+        assert_eq!(stats.longest_decrease, 4..6);
+        assert_eq!(stats.plateaus, 2);
+        assert_eq!(stats.anomalies, vec![(4, 50), (8, 12), (9, 13)]);
+
+        // sums 607, 618, 618, 617, 647, 716, 769, 792 of the worked example
+        let depths = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+        let stats = trends(depths, 3, 4, 1.0);
+        assert_eq!(stats.longest_increase, 3..8);
+        assert_eq!(stats.longest_decrease, 2..4);
+        assert_eq!(stats.plateaus, 1);
+        assert_eq!(
+            stats.anomalies,
+            vec![(4, 647), (5, 716), (6, 769), (7, 792)]
+        );
     }
 }
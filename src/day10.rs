@@ -1,6 +1,9 @@
+use crate::error::{parse_lines, ParseError};
+use crate::solution::{Answer, Solution};
 use itertools::Itertools;
 use std::collections::VecDeque;
-use std::fs;
+
+pub struct Day10;
 
 fn corrupted_char_value(c: char) -> u32 {
     match c {
@@ -39,9 +42,7 @@ fn corrupted_line_value(line: &str) -> Option<u32> {
         match c {
             '(' | '[' | '{' | '<' => deque.push_front(c),
             _ => {
-                let d: Option<char> = deque.pop_front();
-                if d.is_some() {
-                    let d_char: char = d.unwrap();
+                if let Some(d_char) = deque.pop_front() {
                     if d_char != expected_symbol(c) {
                         return Some(corrupted_char_value(c));
                     }
@@ -77,37 +78,53 @@ fn autocomplete_value(line: &str) -> u64 {
     value
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day10.txt").expect("Could not read file");
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    type Input = Vec<String>;
 
-    input_str
-        .lines()
-        .map(|ln| corrupted_line_value(ln))
-        .filter(|opt| opt.is_some())
-        .map(|opt| opt.unwrap())
-        .fold(0, |acc, k| acc + k)
-        .to_string()
-}
+    fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+        let lines = parse_lines(input, |ln| {
+            match ln.char_indices().find(|&(_, c)| !"()[]{}<>".contains(c)) {
+                Some((k, c)) => Err(ParseError::at(ln, &ln[k..k + c.len_utf8()], "bracket")),
+                None => Ok(String::from(ln)),
+            }
+        })?;
+
+        if lines.is_empty() {
+            return Err(ParseError::at_end(input, "line of brackets"));
+        }
+        Ok(lines)
+    }
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day10.txt").expect("Could not read file");
+    fn part1(lines: &Vec<String>) -> Answer {
+        Ok(lines
+            .iter()
+            .filter_map(|ln| corrupted_line_value(ln))
+            .sum::<u32>()
+            .to_string())
+    }
 
-    let values: Vec<u64> = input_str
-        .lines()
-        .filter(|ln| corrupted_line_value(ln).is_none())
-        .map(|ln| autocomplete_value(ln))
-        .sorted()
-        .collect();
+    fn part2(lines: &Vec<String>) -> Answer {
+        let values: Vec<u64> = lines
+            .iter()
+            .filter(|ln| corrupted_line_value(ln).is_none())
+            .map(|ln| autocomplete_value(ln))
+            .sorted()
+            .collect();
 
-    values[values.len() / 2].to_string()
+        Ok(values[values.len() / 2].to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day10;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "339477");
-        assert_eq!(ex2(), "3049320156");
+        let input = Day10::parse(&fs::read_to_string("inputs/day10.txt").unwrap()).unwrap();
+        assert_eq!(Day10::part1(&input).unwrap(), "339477");
+        assert_eq!(Day10::part2(&input).unwrap(), "3049320156");
     }
 }
@@ -1,7 +1,12 @@
-use std::fs;
+use crate::error::ParseError;
+use crate::solution::{Answer, Solution};
 
-struct Grid<T> {
-    data: [T; 100],
+pub struct Day11;
+
+#[derive(Clone)]
+pub struct Grid<T> {
+    /// Values of the 10x10 grid listed row by row.
+    pub data: [T; 100],
 }
 
 impl<T: Copy> Grid<T> {
@@ -27,26 +32,41 @@ impl<T: Copy> Grid<T> {
 impl Grid<u8> {
     #[allow(dead_code)]
     fn draw(&self) {
-        let mut iter = self.data.iter().enumerate();
-        while let Some((k, value)) = iter.next() {
+        let iter = self.data.iter().enumerate();
+        for (k, value) in iter {
             print!("{}", value);
             if (k + 1) % 10 == 0 {
-                println!("");
+                println!();
             }
         }
-        println!("");
+        println!();
     }
 
-    fn from(s: &String) -> Grid<u8> {
-        let data: [u8; 100] = s
-            .chars()
-            .filter(|c| *c != '\n')
-            .map(|c| c.to_digit(10).unwrap() as u8)
-            .collect::<Vec<u8>>()
-            .try_into()
-            .unwrap();
+    fn from(s: &str) -> Result<Grid<u8>, ParseError> {
+        let mut data = [0u8; 100];
+        let mut lines = s.lines();
+
+        for row in data.chunks_mut(10) {
+            let line = lines
+                .next()
+                .ok_or_else(|| ParseError::at_end(s, "row of 10 energy levels"))?;
+            if line.len() != 10 {
+                return Err(ParseError::at(s, line, "row of 10 energy levels"));
+            }
+            // a row with a multi-byte character has fewer than 10 characters, one of which is
+            // not a digit
+            for (value, (k, c)) in row.iter_mut().zip(line.char_indices()) {
+                *value = c.to_digit(10).ok_or_else(|| {
+                    ParseError::at(s, &line[k..k + c.len_utf8()], "energy level digit")
+                })? as u8;
+            }
+        }
+
+        if let Some(line) = lines.next() {
+            return Err(ParseError::at(s, line, "end of the grid"));
+        }
 
-        Grid { data: data }
+        Ok(Grid { data })
     }
 }
 
@@ -119,38 +139,48 @@ fn step(grid: &mut Grid<u8>) -> u8 {
     flashes.data.iter().filter(|b| **b).count() as u8
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day11.txt").expect("Could not read file");
-    let mut grid: Grid<u8> = Grid::from(&input_str);
-    let mut total_flashes: u32 = 0;
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    type Input = Grid<u8>;
 
-    for _ in 0..100 {
-        total_flashes += step(&mut grid) as u32;
+    fn parse(input: &str) -> Result<Grid<u8>, ParseError> {
+        Grid::from(input)
     }
 
-    total_flashes.to_string()
-}
+    fn part1(grid: &Grid<u8>) -> Answer {
+        let mut grid: Grid<u8> = grid.clone();
+        let mut total_flashes: u32 = 0;
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day11.txt").expect("Could not read file");
-    let mut grid: Grid<u8> = Grid::from(&input_str);
-    let mut step_count: u32 = 0;
+        for _ in 0..100 {
+            total_flashes += step(&mut grid) as u32;
+        }
 
-    let mut all_flashed: bool = false;
-    while !all_flashed {
-        all_flashed = step(&mut grid) == 100;
-        step_count += 1;
+        Ok(total_flashes.to_string())
     }
 
-    step_count.to_string()
+    fn part2(grid: &Grid<u8>) -> Answer {
+        let mut grid: Grid<u8> = grid.clone();
+        let mut step_count: u32 = 0;
+
+        let mut all_flashed: bool = false;
+        while !all_flashed {
+            all_flashed = step(&mut grid) == 100;
+            step_count += 1;
+        }
+
+        Ok(step_count.to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day11;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "1594");
-        assert_eq!(ex2(), "437");
+        let input = Day11::parse(&fs::read_to_string("inputs/day11.txt").unwrap()).unwrap();
+        assert_eq!(Day11::part1(&input).unwrap(), "1594");
+        assert_eq!(Day11::part2(&input).unwrap(), "437");
     }
 }
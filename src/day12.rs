@@ -1,26 +1,30 @@
+use crate::error::ParseError;
+use crate::solution::{Answer, Solution};
 use itertools::Itertools;
 use std::collections::HashMap;
-use std::fs;
+
+pub struct Day12;
 
 /// vertices are in this case denoted by integers rather than by strings
-type Vertex = u16;
+pub type Vertex = u16;
 
-///
-struct Graph {
+/// cave system together with the state of the current DFS path
+#[derive(Clone)]
+pub struct Graph {
     /// a vector of vertices reachable for each existing vertex
     /// (oriented edges)
-    edges: HashMap<Vertex, Vec<Vertex>>,
+    pub edges: HashMap<Vertex, Vec<Vertex>>,
     /// can a particular vertex be revisited without limitations?
     /// (large caves)
-    is_revisitable: HashMap<Vertex, bool>,
+    pub is_revisitable: HashMap<Vertex, bool>,
     /// how many times was given vertex visited on the current path
-    open_count: HashMap<Vertex, u16>,
+    pub open_count: HashMap<Vertex, u16>,
 }
 
-const START_VERTEX: u16 = 0;
-const END_VERTEX: u16 = 1;
+pub const START_VERTEX: Vertex = 0;
+pub const END_VERTEX: Vertex = 1;
 
-fn init_graph(s: String) -> Graph {
+fn init_graph(s: &str) -> Result<Graph, ParseError> {
     let mut edges: HashMap<Vertex, Vec<Vertex>> = HashMap::new();
     let mut is_revisitable: HashMap<Vertex, bool> = HashMap::new();
     let mut open_count: HashMap<Vertex, u16> = HashMap::new();
@@ -31,8 +35,12 @@ fn init_graph(s: String) -> Graph {
     let mut b: u16;
 
     // process each defined edge
-    let mut v = s.lines().map(|s| s.split("-").collect_tuple().unwrap());
-    while let Some((sa, sb)) = v.next() {
+    for line in s.lines() {
+        let (sa, sb) = line
+            .split('-')
+            .collect_tuple()
+            .ok_or_else(|| ParseError::at(s, line, "edge in the form 'a-b'"))?;
+
         // add a new vertex identifier if necessary
         if !vertex_names.contains_key(sa) {
             vertex_names.insert(sa, vertex_names.len() as Vertex);
@@ -45,40 +53,39 @@ fn init_graph(s: String) -> Graph {
         b = *vertex_names.get(sb).unwrap();
 
         // extend the `is_revisitable` map
-        if !is_revisitable.contains_key(&a) {
-            is_revisitable.insert(a, sa.chars().map(|c| c.is_uppercase()).all(|b| b));
-        }
-        if !is_revisitable.contains_key(&b) {
-            is_revisitable.insert(b, sb.chars().map(|c| c.is_uppercase()).all(|b| b));
-        }
+        is_revisitable
+            .entry(a)
+            .or_insert_with(|| sa.chars().all(|c| c.is_uppercase()));
+        is_revisitable
+            .entry(b)
+            .or_insert_with(|| sb.chars().all(|c| c.is_uppercase()));
 
         // extend the `open_count` map
-        if !open_count.contains_key(&a) {
-            open_count.insert(a, 0);
-        }
-        if !open_count.contains_key(&b) {
-            open_count.insert(b, 0);
-        }
+        open_count.entry(a).or_insert(0);
+        open_count.entry(b).or_insert(0);
         // insert an edge a->b
-        if !edges.contains_key(&a) {
-            edges.insert(a, Vec::new());
-        }
-        edges.get_mut(&a).unwrap().push(b);
+        edges.entry(a).or_default().push(b);
 
         // insert an edge b->a
-        if !edges.contains_key(&b) {
-            edges.insert(b, Vec::new());
+        edges.entry(b).or_default().push(a);
+    }
+
+    for (name, vertex) in [("start", START_VERTEX), ("end", END_VERTEX)] {
+        if !edges.contains_key(&vertex) {
+            return Err(ParseError::at_end(
+                s,
+                &format!("edge connected to '{}'", name),
+            ));
         }
-        edges.get_mut(&b).unwrap().push(a);
     }
 
     // return the starting position of a graph before a DFS is run
     // as defined by the input
-    Graph {
-        edges: edges,
-        is_revisitable: is_revisitable,
-        open_count: open_count,
-    }
+    Ok(Graph {
+        edges,
+        is_revisitable,
+        open_count,
+    })
 }
 
 fn can_visit_ex1(g: &Graph, v: Vertex) -> bool {
@@ -94,15 +101,13 @@ fn can_visit_ex2(g: &Graph, v: Vertex) -> bool {
 
     let mut small_cave_visited: bool = false;
     for (vtx, _) in g.is_revisitable.iter().filter(|(_, val)| !(**val)) {
-        small_cave_visited |= *g.open_count.get(&vtx).unwrap() == 2;
+        small_cave_visited |= *g.open_count.get(vtx).unwrap() == 2;
     }
 
     // or it is a starting vertex, and then it cannot be visited again ever ...
-    let cutoff = if v == START_VERTEX {
-        0
     // or it is a small cave but a different small cave was already visited,
     // so it can be visited only for the first time ...
-    } else if small_cave_visited {
+    let cutoff = if v == START_VERTEX || small_cave_visited {
         0
     // or it is a small cave that can be visited even a second time
     } else {
@@ -139,26 +144,32 @@ fn count_paths(mut g: Graph, can_visit_fn: fn(&Graph, Vertex) -> bool) -> u32 {
     dfs(&mut g, START_VERTEX, can_visit_fn)
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day12.txt").expect("Could not read file");
-    let g = init_graph(input_str);
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    type Input = Graph;
 
-    count_paths(g, can_visit_ex1).to_string()
-}
+    fn parse(input: &str) -> Result<Graph, ParseError> {
+        init_graph(input)
+    }
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day12.txt").expect("Could not read file");
-    let g = init_graph(input_str);
+    fn part1(g: &Graph) -> Answer {
+        Ok(count_paths(g.clone(), can_visit_ex1).to_string())
+    }
 
-    count_paths(g, can_visit_ex2).to_string()
+    fn part2(g: &Graph) -> Answer {
+        Ok(count_paths(g.clone(), can_visit_ex2).to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day12;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "4773");
-        assert_eq!(ex2(), "116985");
+        let input = Day12::parse(&fs::read_to_string("inputs/day12.txt").unwrap()).unwrap();
+        assert_eq!(Day12::part1(&input).unwrap(), "4773");
+        assert_eq!(Day12::part2(&input).unwrap(), "116985");
     }
 }
@@ -1,44 +1,75 @@
+use crate::error::{parse_token, ParseError};
+use crate::solution::{Answer, Solution};
 use regex::{Captures, Regex};
 use std::collections::HashSet;
-use std::fs;
+
+pub struct Day13;
 
 #[derive(Debug)]
-enum Fold {
+pub enum Fold {
     Horizontal(Coord),
     Vertical(Coord),
 }
 
-type Coord = u16;
-type Points = HashSet<(Coord, Coord)>;
-type Folds = Vec<Fold>;
-
-fn load_configuration(s: String) -> (Points, Folds) {
-    let mut iter = s.lines();
+pub type Coord = u16;
+pub type Points = HashSet<(Coord, Coord)>;
+pub type Folds = Vec<Fold>;
+
+/// Capital letters of the font used for the activation code. Each letter is 4 pixels wide
+/// and 6 pixels tall, listed here row by row. Consecutive letters are separated by one
+/// empty column.
+const LETTER_WIDTH: usize = 4;
+const LETTER_HEIGHT: usize = 6;
+const LETTERS: [(char, &str); 16] = [
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Z', "####...#..#..#..#...####"),
+];
+
+fn load_configuration(input: &str) -> Result<(Points, Folds), ParseError> {
+    let mut iter = input.lines();
     let mut caps: Captures;
 
     let mut points: Points = HashSet::new();
-    let re_points = Regex::new(r"(\d+),(\d+)").unwrap();
-    while let Some(s) = iter.next() {
-        if s == "" {
+    let re_points = Regex::new(r"^(\d+),(\d+)$").unwrap();
+    for s in iter.by_ref() {
+        if s.is_empty() {
             break;
         }
 
-        caps = re_points.captures(s).unwrap();
+        caps = re_points
+            .captures(s)
+            .ok_or_else(|| ParseError::at(input, s, "point in the form 'x,y'"))?;
         points.insert((
-            caps.get(1).unwrap().as_str().parse::<Coord>().unwrap(),
-            caps.get(2).unwrap().as_str().parse::<Coord>().unwrap(),
+            parse_token(input, caps.get(1).unwrap().as_str(), "coordinate")?,
+            parse_token(input, caps.get(2).unwrap().as_str(), "coordinate")?,
         ));
     }
 
     let mut folds: Folds = Vec::new();
-    let re_folds = Regex::new(r"fold along ([x,y])=(\d+)").unwrap();
+    let re_folds = Regex::new(r"^fold along ([xy])=(\d+)$").unwrap();
     let mut alignment: &str;
     let mut coord: Coord;
-    while let Some(s) = iter.next() {
-        caps = re_folds.captures(s).unwrap();
+    for s in iter {
+        caps = re_folds
+            .captures(s)
+            .ok_or_else(|| ParseError::at(input, s, "fold instruction"))?;
 
         alignment = caps.get(1).unwrap().as_str();
-        coord = caps.get(2).unwrap().as_str().parse::<Coord>().unwrap();
+        coord = parse_token(input, caps.get(2).unwrap().as_str(), "coordinate")?;
         folds.push(if alignment == "x" {
             Fold::Vertical(coord)
         } else {
@@ -46,7 +77,11 @@ fn load_configuration(s: String) -> (Points, Folds) {
         });
     }
 
-    (points, folds)
+    if folds.is_empty() {
+        return Err(ParseError::at_end(input, "fold instruction"));
+    }
+
+    Ok((points, folds))
 }
 
 fn fold_paper(points: Points, fold: &Fold) -> Points {
@@ -76,10 +111,9 @@ fn fold_paper(points: Points, fold: &Fold) -> Points {
     new_points
 }
 
-#[allow(dead_code)]
 fn draw_points(points: Points) -> String {
     let width: usize = points.iter().map(|(x, _)| *x).max().unwrap() as usize + 1;
-    let height: usize = points.iter().map(|(x, _)| *x).max().unwrap() as usize + 1;
+    let height: usize = points.iter().map(|(_, y)| *y).max().unwrap() as usize + 1;
 
     // initialize grid
     let mut grid: Vec<Vec<bool>> = Vec::new();
@@ -99,44 +133,76 @@ fn draw_points(points: Points) -> String {
 
     // draw the entire grid to a string and return it
     let mut s: String = String::new();
-    for y in 0..height {
-        for x in 0..width {
-            s.push(if grid[y][x] { '#' } else { '.' });
+    for row in grid {
+        for is_marked in row {
+            s.push(if is_marked { '#' } else { '.' });
         }
-        s.push_str("\n");
+        s.push('\n');
     }
     s
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day13.txt").expect("Could not read file");
-    let (points, folds) = load_configuration(input_str);
+fn read_letters(drawing: &str) -> Option<String> {
+    let rows: Vec<&str> = drawing.lines().collect();
+    let width: usize = rows.first()?.len();
+    if rows.len() != LETTER_HEIGHT || !(width + 1).is_multiple_of(LETTER_WIDTH + 1) {
+        return None;
+    }
 
-    fold_paper(points, folds.iter().next().unwrap())
-        .len()
-        .to_string()
+    // cut out the pixels of each letter and look them up in the font
+    (0..(width + 1) / (LETTER_WIDTH + 1))
+        .map(|k| {
+            let from = k * (LETTER_WIDTH + 1);
+            let glyph: String = rows
+                .iter()
+                .map(|row| row.get(from..from + LETTER_WIDTH))
+                .collect::<Option<String>>()?;
+
+            LETTERS
+                .iter()
+                .find(|(_, pixels)| *pixels == glyph)
+                .map(|(c, _)| *c)
+        })
+        .collect()
 }
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day13.txt").expect("Could not read file");
-    let (mut points, folds) = load_configuration(input_str);
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+    type Input = (Points, Folds);
 
-    for fold in folds {
-        points = fold_paper(points, &fold);
+    fn parse(input: &str) -> Result<(Points, Folds), ParseError> {
+        load_configuration(input)
     }
 
-    // utilization of an external visual processing unit (aka eyes) here
-    // println!("{}", draw_points(points));
+    fn part1((points, folds): &(Points, Folds)) -> Answer {
+        Ok(fold_paper(points.clone(), folds.first().unwrap())
+            .len()
+            .to_string())
+    }
+
+    fn part2((points, folds): &(Points, Folds)) -> Answer {
+        let mut points: Points = points.clone();
 
-    String::from("RZKZLPGH")
+        for fold in folds {
+            points = fold_paper(points, fold);
+        }
+
+        // read the code from the drawing, fall back to an external visual processing
+        // unit (aka eyes) for shapes that are not letters of the known font
+        let drawing = draw_points(points);
+        Ok(read_letters(&drawing).unwrap_or(drawing))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day13;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "765");
-        assert_eq!(ex2(), "RZKZLPGH");
+        let input = Day13::parse(&fs::read_to_string("inputs/day13.txt").unwrap()).unwrap();
+        assert_eq!(Day13::part1(&input).unwrap(), "765");
+        assert_eq!(Day13::part2(&input).unwrap(), "RZKZLPGH");
     }
 }
@@ -1,26 +1,36 @@
+use crate::error::ParseError;
+use crate::solution::{Answer, Solution};
 use itertools::Itertools;
 use regex::{Captures, Regex};
 use std::collections::HashMap;
-use std::fs;
 
-type Rules = HashMap<(char, char), char>;
+pub struct Day14;
+
+pub type Rules = HashMap<(char, char), char>;
 type PairCounts = HashMap<(char, char), u64>;
 
-fn load_configuration(s: String) -> (String, Rules) {
+fn load_configuration(s: &str) -> Result<(String, Rules), ParseError> {
     let mut lines = s.lines();
 
     // load starting polymer configuration
-    let polymer = String::from(lines.next().unwrap());
+    let polymer = lines
+        .next()
+        .filter(|ln| !ln.is_empty() && ln.chars().all(|c| c.is_ascii_uppercase()))
+        .ok_or_else(|| ParseError::at(s, s.lines().next().unwrap_or(s), "polymer template"))?;
 
     // skip empty line
-    lines.next();
+    if let Some(ln) = lines.next().filter(|ln| !ln.is_empty()) {
+        return Err(ParseError::at(s, ln, "empty line"));
+    }
 
     // load pair insertion rules
     let mut rules = HashMap::new();
     let mut caps: Captures;
-    let re_rule = Regex::new(r"([A-Z])([A-Z]) -> ([A-Z])").unwrap();
-    while let Some(rule_str) = lines.next() {
-        caps = re_rule.captures(rule_str).unwrap();
+    let re_rule = Regex::new(r"^([A-Z])([A-Z]) -> ([A-Z])$").unwrap();
+    for rule_str in lines {
+        caps = re_rule
+            .captures(rule_str)
+            .ok_or_else(|| ParseError::at(s, rule_str, "pair insertion rule"))?;
         rules.insert(
             (
                 caps.get(1).unwrap().as_str().parse::<char>().unwrap(),
@@ -30,7 +40,7 @@ fn load_configuration(s: String) -> (String, Rules) {
         );
     }
 
-    (polymer, rules)
+    Ok((String::from(polymer), rules))
 }
 
 fn original_pair_counts(s: &str) -> PairCounts {
@@ -72,39 +82,46 @@ fn occurence_counts(pair_counts: PairCounts, s: &str) -> HashMap<char, u64> {
     map
 }
 
-fn general_solution(step_count: u16) -> String {
-    let input_str: String = fs::read_to_string("inputs/day14.txt").expect("Could not read file");
-    let (s, rules) = load_configuration(input_str);
-    let mut pair_counts = original_pair_counts(&s);
+fn general_solution((s, rules): &(String, Rules), step_count: u16) -> String {
+    let mut pair_counts = original_pair_counts(s);
 
     for _ in 0..step_count {
-        pair_counts = next_step(pair_counts, &rules);
+        pair_counts = next_step(pair_counts, rules);
     }
 
-    let counts = occurence_counts(pair_counts, &s);
-    let mut occurence_iter = counts
-        .iter()
-        .map(|(_, v)| v)
-        .sorted_by(|v1, v2| Ord::cmp(&v1, &v2));
+    let counts = occurence_counts(pair_counts, s);
+    let mut occurence_iter = counts.values().sorted_by(|v1, v2| Ord::cmp(&v1, &v2));
     let first = occurence_iter.next().unwrap();
     let last = occurence_iter.last().unwrap();
     (last - first).to_string()
 }
 
-pub fn ex1() -> String {
-    general_solution(10)
-}
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    type Input = (String, Rules);
+
+    fn parse(input: &str) -> Result<(String, Rules), ParseError> {
+        load_configuration(input)
+    }
+
+    fn part1(configuration: &(String, Rules)) -> Answer {
+        Ok(general_solution(configuration, 10))
+    }
 
-pub fn ex2() -> String {
-    general_solution(40)
+    fn part2(configuration: &(String, Rules)) -> Answer {
+        Ok(general_solution(configuration, 40))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day14;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "2112");
-        assert_eq!(ex2(), "3243771149914");
+        let input = Day14::parse(&fs::read_to_string("inputs/day14.txt").unwrap()).unwrap();
+        assert_eq!(Day14::part1(&input).unwrap(), "2112");
+        assert_eq!(Day14::part2(&input).unwrap(), "3243771149914");
     }
 }
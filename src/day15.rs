@@ -1,18 +1,47 @@
-use std::fs;
+use crate::error::ParseError;
+use crate::solution::{Answer, Solution};
 
-type BaseRisks = Vec<Vec<u8>>;
+pub struct Day15;
+
+pub type BaseRisks = Vec<Vec<u8>>;
 type TotalRisks = Vec<Vec<Option<u16>>>;
 
-fn process_line(ln: &str) -> Vec<u8> {
-    ln.chars().map(|c| c.to_digit(10).unwrap() as u8).collect()
+fn process_line(ln: &str) -> Result<Vec<u8>, ParseError> {
+    ln.char_indices()
+        .map(|(k, c)| match c.to_digit(10) {
+            Some(d) => Ok(d as u8),
+            None => Err(ParseError::at(
+                ln,
+                &ln[k..k + c.len_utf8()],
+                "risk level digit",
+            )),
+        })
+        .collect()
 }
 
-fn load_base_risks_ex1(s: String) -> BaseRisks {
-    s.lines().map(|ln| process_line(ln)).collect::<BaseRisks>()
+fn load_base_risks(s: &str) -> Result<BaseRisks, ParseError> {
+    let mut risks = BaseRisks::new();
+
+    for (k, line) in s.lines().enumerate() {
+        let row = process_line(line).map_err(|e| e.offset_lines(k))?;
+        if row.is_empty() || risks.first().is_some_and(|first| first.len() != row.len()) {
+            return Err(ParseError::at(
+                s,
+                line,
+                "row of the same length as the first one",
+            ));
+        }
+        risks.push(row);
+    }
+
+    if risks.is_empty() {
+        return Err(ParseError::at_end(s, "row of risk levels"));
+    }
+
+    Ok(risks)
 }
 
-fn load_base_risks_ex2(s: String) -> BaseRisks {
-    let original_grid = load_base_risks_ex1(s);
+fn extend_base_risks(original_grid: &BaseRisks) -> BaseRisks {
     let mut new_grid: BaseRisks = Vec::new();
 
     // allocate space in the new grid
@@ -62,11 +91,11 @@ fn initial_total_risks(height: usize, width: usize) -> TotalRisks {
 }
 
 fn updated_val(old: Option<u16>, neighbour: Option<u16>, risk: u8) -> Option<u16> {
-    if neighbour == None {
+    if neighbour.is_none() {
         return old;
     }
 
-    if old == None {
+    if old.is_none() {
         return Some(neighbour.unwrap() + risk as u16);
     }
 
@@ -111,14 +140,11 @@ fn update_total_risks(totals: &mut TotalRisks, base: &BaseRisks) -> bool {
     totals_updated
 }
 
-fn general_solution(load_base_risks_fn: fn(String) -> BaseRisks) -> String {
-    let input_str: String = fs::read_to_string("inputs/day15.txt").expect("Could not read file");
-    let base: BaseRisks = load_base_risks_fn(input_str);
-
+fn general_solution(base: &BaseRisks) -> String {
     // keep iterating and updating the board as long as at least one field is changed
     let mut total_risks: TotalRisks = initial_total_risks(base.len(), base[0].len());
     loop {
-        if !update_total_risks(&mut total_risks, &base) {
+        if !update_total_risks(&mut total_risks, base) {
             break;
         }
     }
@@ -128,20 +154,32 @@ fn general_solution(load_base_risks_fn: fn(String) -> BaseRisks) -> String {
     vec[vec.len() - 1].unwrap().to_string()
 }
 
-pub fn ex1() -> String {
-    general_solution(load_base_risks_ex1)
-}
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    type Input = BaseRisks;
+
+    fn parse(input: &str) -> Result<BaseRisks, ParseError> {
+        load_base_risks(input)
+    }
 
-pub fn ex2() -> String {
-    general_solution(load_base_risks_ex2)
+    fn part1(base: &BaseRisks) -> Answer {
+        Ok(general_solution(base))
+    }
+
+    fn part2(base: &BaseRisks) -> Answer {
+        Ok(general_solution(&extend_base_risks(base)))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day15;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "503");
-        assert_eq!(ex2(), "2853");
+        let input = Day15::parse(&fs::read_to_string("inputs/day15.txt").unwrap()).unwrap();
+        assert_eq!(Day15::part1(&input).unwrap(), "503");
+        assert_eq!(Day15::part2(&input).unwrap(), "2853");
     }
 }
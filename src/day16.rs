@@ -1,11 +1,14 @@
+use crate::error::ParseError;
+use crate::solution::{Answer, Solution};
 use std::collections::VecDeque;
-use std::fs;
+
+pub struct Day16;
 
 type Bit = u8;
 type BitStream = VecDeque<Bit>;
 
 #[derive(Debug)]
-enum OpType {
+pub enum OpType {
     Sum,
     Product,
     Mininum,
@@ -16,7 +19,7 @@ enum OpType {
 }
 
 #[derive(Debug)]
-enum Packet {
+pub enum Packet {
     Literal {
         version: u64,
         value: u64,
@@ -28,30 +31,30 @@ enum Packet {
     },
 }
 
-fn create_bit_stream(s: String) -> BitStream {
-    let iter = s
-        .chars()
-        .filter(|c| c.is_alphanumeric())
-        .map(|c| c.to_ascii_lowercase().to_digit(16).unwrap() as u8);
+fn create_bit_stream(s: &str) -> Result<BitStream, ParseError> {
     let mut stream: BitStream = BitStream::new();
 
-    for v in iter {
+    for (k, c) in s.char_indices().filter(|(_, c)| !c.is_whitespace()) {
+        let v = c
+            .to_digit(16)
+            .ok_or_else(|| ParseError::at(s, &s[k..k + c.len_utf8()], "hexadecimal digit"))?;
         for k in 0..4 {
-            stream.push_back((v >> (3 - k)) % 2);
+            stream.push_back(((v >> (3 - k)) % 2) as Bit);
         }
     }
 
-    stream
+    Ok(stream)
 }
 
-fn get(s: &mut BitStream, k: usize) -> u64 {
+/// Reads `k` bits as a number, `None` if the stream ends prematurely.
+fn get(s: &mut BitStream, k: usize) -> Option<u64> {
     let mut v: u64 = 0;
 
     for _ in 0..k {
-        v = 2 * v + s.pop_front().unwrap() as u64;
+        v = 2 * v + s.pop_front()? as u64;
     }
 
-    v
+    Some(v)
 }
 
 fn get_op_type(id: u64) -> OpType {
@@ -67,63 +70,64 @@ fn get_op_type(id: u64) -> OpType {
     }
 }
 
-fn get_literal_value(s: &mut BitStream) -> u64 {
+fn get_literal_value(s: &mut BitStream) -> Option<u64> {
     let mut value_stream: BitStream = BitStream::new();
 
     while let Some(1) = s.pop_front() {
         for _ in 0..4 {
-            value_stream.push_back(s.pop_front().unwrap());
+            value_stream.push_back(s.pop_front()?);
         }
     }
 
     for _ in 0..4 {
-        value_stream.push_back(s.pop_front().unwrap());
+        value_stream.push_back(s.pop_front()?);
     }
 
     let stream_len = value_stream.len();
     get(&mut value_stream, stream_len)
 }
 
-fn get_subpackets(s: &mut BitStream) -> Vec<Packet> {
-    if get(s, 1) == 0 {
-        let bit_length = get(s, 15);
+fn get_subpackets(s: &mut BitStream) -> Option<Vec<Packet>> {
+    if get(s, 1)? == 0 {
+        let bit_length = get(s, 15)?;
         let mut args: Vec<Packet> = Vec::new();
 
         let mut temp_s: BitStream = BitStream::new();
         for _ in 0..bit_length {
-            temp_s.push_back(s.pop_front().unwrap());
+            temp_s.push_back(s.pop_front()?);
         }
 
-        while temp_s.len() != 0 {
-            args.push(get_packet(&mut temp_s));
+        while !temp_s.is_empty() {
+            args.push(get_packet(&mut temp_s)?);
         }
 
-        return args;
+        Some(args)
     } else {
-        let packet_count = get(s, 11);
-        return (0..packet_count).map(|_| get_packet(s)).collect();
+        let packet_count = get(s, 11)?;
+        (0..packet_count).map(|_| get_packet(s)).collect()
     }
 }
 
-fn get_packet(s: &mut BitStream) -> Packet {
-    let version = get(s, 3);
-    let type_id = get(s, 3);
+fn get_packet(s: &mut BitStream) -> Option<Packet> {
+    let version = get(s, 3)?;
+    let type_id = get(s, 3)?;
 
     if type_id == 4 {
-        Packet::Literal {
-            version: version,
-            value: get_literal_value(s),
-        }
+        Some(Packet::Literal {
+            version,
+            value: get_literal_value(s)?,
+        })
     } else {
-        Packet::Op {
-            version: version,
+        Some(Packet::Op {
+            version,
             op_type: get_op_type(type_id),
-            args: get_subpackets(s),
-        }
+            args: get_subpackets(s)?,
+        })
     }
 }
 
-fn sum_version_numbers(packet: &Packet) -> u64 {
+/// Sum of the versions of the packet and all of its subpackets.
+pub fn sum_version_numbers(packet: &Packet) -> u64 {
     match packet {
         Packet::Literal { version, value: _ } => *version,
         Packet::Op {
@@ -134,14 +138,15 @@ fn sum_version_numbers(packet: &Packet) -> u64 {
             *version
                 + args
                     .iter()
-                    .map(|p| sum_version_numbers(p))
+                    .map(sum_version_numbers)
                     .reduce(|acc, k| acc + k)
                     .unwrap()
         }
     }
 }
 
-fn eval(packet: &Packet) -> u64 {
+/// Value of the expression represented by the packet.
+pub fn eval(packet: &Packet) -> u64 {
     match packet {
         Packet::Literal { version: _, value } => *value,
         Packet::Op {
@@ -149,10 +154,10 @@ fn eval(packet: &Packet) -> u64 {
             op_type,
             args,
         } => match op_type {
-            OpType::Sum => args.iter().map(|p| eval(p)).sum(),
-            OpType::Product => args.iter().map(|p| eval(p)).product(),
-            OpType::Mininum => args.iter().map(|p| eval(p)).min().unwrap(),
-            OpType::Maximum => args.iter().map(|p| eval(p)).max().unwrap(),
+            OpType::Sum => args.iter().map(eval).sum(),
+            OpType::Product => args.iter().map(eval).product(),
+            OpType::Mininum => args.iter().map(eval).min().unwrap(),
+            OpType::Maximum => args.iter().map(eval).max().unwrap(),
             OpType::GreaterThan => {
                 if eval(&args[0]) > eval(&args[1]) {
                     1
@@ -178,26 +183,33 @@ fn eval(packet: &Packet) -> u64 {
     }
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day16.txt").expect("Could not read file");
-    let mut stream = create_bit_stream(input_str);
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+    type Input = Packet;
 
-    sum_version_numbers(&get_packet(&mut stream)).to_string()
-}
+    fn parse(input: &str) -> Result<Packet, ParseError> {
+        get_packet(&mut create_bit_stream(input)?)
+            .ok_or_else(|| ParseError::at_end(input.trim_end(), "remaining packet bits"))
+    }
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day16.txt").expect("Could not read file");
-    let mut stream = create_bit_stream(input_str);
+    fn part1(packet: &Packet) -> Answer {
+        Ok(sum_version_numbers(packet).to_string())
+    }
 
-    eval(&get_packet(&mut stream)).to_string()
+    fn part2(packet: &Packet) -> Answer {
+        Ok(eval(packet).to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day16;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "920");
-        assert_eq!(ex2(), "10185143721112");
+        let input = Day16::parse(&fs::read_to_string("inputs/day16.txt").unwrap()).unwrap();
+        assert_eq!(Day16::part1(&input).unwrap(), "920");
+        assert_eq!(Day16::part2(&input).unwrap(), "10185143721112");
     }
 }
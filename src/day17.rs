@@ -1,12 +1,15 @@
+use crate::error::{parse_token, ParseError};
+use crate::solution::{Answer, Solution};
 use regex::Regex;
-use std::fs;
+
+pub struct Day17;
 
 #[derive(Debug)]
-struct TargetArea {
-    min_x: i32,
-    max_x: i32,
-    min_y: i32,
-    max_y: i32,
+pub struct TargetArea {
+    pub min_x: i32,
+    pub max_x: i32,
+    pub min_y: i32,
+    pub max_y: i32,
 }
 
 #[derive(Debug)]
@@ -36,16 +39,19 @@ impl Vec2D {
     }
 }
 
-fn load_target_area(s: String) -> TargetArea {
+fn load_target_area(s: &str) -> Result<TargetArea, ParseError> {
     let re = Regex::new(r"target area: x=(-?\d+)\.\.(-?\d+), y=(-?\d+)\.\.(-?\d+)").unwrap();
-    let caps = re.captures(&s).unwrap();
-
-    TargetArea {
-        min_x: caps.get(1).unwrap().as_str().parse::<i32>().unwrap(),
-        max_x: caps.get(2).unwrap().as_str().parse::<i32>().unwrap(),
-        min_y: caps.get(3).unwrap().as_str().parse::<i32>().unwrap(),
-        max_y: caps.get(4).unwrap().as_str().parse::<i32>().unwrap(),
-    }
+    let caps = re
+        .captures(s)
+        .ok_or_else(|| ParseError::at(s, s.trim_end(), "'target area: x=A..B, y=C..D'"))?;
+    let coord = |k| parse_token(s, caps.get(k).unwrap().as_str(), "coordinate");
+
+    Ok(TargetArea {
+        min_x: coord(1)?,
+        max_x: coord(2)?,
+        min_y: coord(3)?,
+        max_y: coord(4)?,
+    })
 }
 
 fn hits_target(velocity: Vec2D, area: &TargetArea) -> Option<i32> {
@@ -96,36 +102,39 @@ fn velocity_vectors(area: &TargetArea) -> impl std::iter::Iterator<Item = Vec2D>
     })
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day17.txt").expect("Could not read file");
-    let target_area = load_target_area(input_str);
-
-    velocity_vectors(&target_area)
-        .map(|v| hits_target(v, &target_area))
-        .filter(|opt| opt.is_some())
-        .map(|opt| opt.unwrap())
-        .max()
-        .unwrap()
-        .to_string()
-}
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    type Input = TargetArea;
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day17.txt").expect("Could not read file");
-    let target_area = load_target_area(input_str);
+    fn parse(input: &str) -> Result<TargetArea, ParseError> {
+        load_target_area(input)
+    }
+
+    fn part1(target_area: &TargetArea) -> Answer {
+        Ok(velocity_vectors(target_area)
+            .filter_map(|v| hits_target(v, target_area))
+            .max()
+            .unwrap()
+            .to_string())
+    }
 
-    velocity_vectors(&target_area)
-        .map(|v| hits_target(v, &target_area))
-        .filter(|opt| opt.is_some())
-        .count()
-        .to_string()
+    fn part2(target_area: &TargetArea) -> Answer {
+        Ok(velocity_vectors(target_area)
+            .filter_map(|v| hits_target(v, target_area))
+            .count()
+            .to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day17;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "10585");
-        assert_eq!(ex2(), "5247");
+        let input = Day17::parse(&fs::read_to_string("inputs/day17.txt").unwrap()).unwrap();
+        assert_eq!(Day17::part1(&input).unwrap(), "10585");
+        assert_eq!(Day17::part2(&input).unwrap(), "5247");
     }
 }
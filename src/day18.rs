@@ -1,43 +1,71 @@
+use crate::error::{parse_lines, parse_token, ParseError};
+use crate::solution::{Answer, Solution};
 use itertools::Itertools;
-use std::fs;
 use std::ops::Add;
 use std::rc::Rc;
 
+pub struct Day18;
+
 #[derive(Debug)]
-enum Number {
+pub enum Number {
     Literal { v: u32 },
     Pair { left: Rc<Number>, right: Rc<Number> },
 }
 
 impl Number {
-    fn from(chars: &mut impl Iterator<Item = char>) -> Self {
-        match chars.next() {
-            Some('[') => {
-                let left = Number::from(chars);
-                let right = Number::from(chars);
+    /// Parses a single snailfish number, e.g. `[[1,2],3]`.
+    pub fn from(line: &str) -> Result<Self, ParseError> {
+        let (number, pos) = Number::parse_at(line, 0)?;
+        match line[pos..].chars().next() {
+            None => Ok(number),
+            Some(c) => Err(ParseError::at(
+                line,
+                &line[pos..pos + c.len_utf8()],
+                "end of line",
+            )),
+        }
+    }
 
-                chars.next(); // get rid of closing ']' symbol
-                return Number::Pair {
+    /// Parses the number starting at byte `pos` of `line`, returns it together with
+    /// the position right after it.
+    fn parse_at(line: &str, pos: usize) -> Result<(Self, usize), ParseError> {
+        let rest = &line[pos..];
+
+        if rest.starts_with('[') {
+            let (left, pos) = Number::parse_at(line, pos + 1)?;
+            let pos = Number::skip_symbol(line, pos, ',')?;
+            let (right, pos) = Number::parse_at(line, pos)?;
+            let pos = Number::skip_symbol(line, pos, ']')?;
+
+            Ok((
+                Number::Pair {
                     left: Rc::new(left),
                     right: Rc::new(right),
-                };
-            }
-            Some(c) if '0' <= c && c <= '9' => {
-                let mut s: String = String::new();
-                s.push(c);
+                },
+                pos,
+            ))
+        } else {
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let token = match len {
+                0 => &rest[..rest.chars().next().map_or(0, char::len_utf8)],
+                _ => &rest[..len],
+            };
+            let v = parse_token(line, token, "'[' or a regular number")?;
 
-                loop {
-                    let c = chars.next().unwrap();
-                    if c == ',' || c == ']' {
-                        return Number::Literal {
-                            v: s.parse::<u32>().unwrap(),
-                        };
-                    }
+            Ok((Number::Literal { v }, pos + len))
+        }
+    }
 
-                    s.push(c);
-                }
-            }
-            _ => panic!("Unknown symbol"),
+    fn skip_symbol(line: &str, pos: usize, symbol: char) -> Result<usize, ParseError> {
+        match line[pos..].chars().next() {
+            Some(c) if c == symbol => Ok(pos + 1),
+            c => Err(ParseError::at(
+                line,
+                &line[pos..pos + c.map_or(0, char::len_utf8)],
+                &format!("'{}'", symbol),
+            )),
         }
     }
 
@@ -57,7 +85,7 @@ impl Number {
         }
     }
 
-    fn magnitude(&self) -> u32 {
+    pub fn magnitude(&self) -> u32 {
         match self {
             Number::Literal { v } => *v,
             Number::Pair { left, right } => 3 * left.magnitude() + 2 * right.magnitude(),
@@ -156,22 +184,21 @@ impl Number {
                 }
 
                 Some(Number::Pair {
-                    left: if left_new.is_some() {
-                        Rc::new(left_new.unwrap())
-                    } else {
-                        left.clone()
+                    left: match left_new {
+                        Some(number) => Rc::new(number),
+                        None => left.clone(),
                     },
-                    right: if right_new.is_some() {
-                        Rc::new(right_new.unwrap())
-                    } else {
-                        right.clone()
+                    right: match right_new {
+                        Some(number) => Rc::new(number),
+                        None => right.clone(),
                     },
                 })
             }
         }
     }
 
-    fn simplify(self) -> Self {
+    /// Reduces the number by repeatedly exploding and splitting it.
+    pub fn simplify(self) -> Self {
         let mut current: Number = self;
         let mut changed: bool;
 
@@ -219,39 +246,51 @@ impl Add for Number {
     }
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day18.txt").expect("Could not read file");
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+    type Input = Vec<Number>;
 
-    input_str
-        .lines()
-        .map(|ln| Number::from(&mut ln.chars()))
-        .reduce(|acc, n| acc + n)
-        .unwrap()
-        .simplify()
-        .magnitude()
-        .to_string()
-}
+    fn parse(input: &str) -> Result<Vec<Number>, ParseError> {
+        let numbers = parse_lines(input, Number::from)?;
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day18.txt").expect("Could not read file");
+        if numbers.len() < 2 {
+            return Err(ParseError::at_end(input, "at least two snailfish numbers"));
+        }
+        Ok(numbers)
+    }
 
-    input_str
-        .lines()
-        .map(|ln| Number::from(&mut ln.chars()))
-        .permutations(2)
-        .map(|v| v[0].clone() + v[1].clone())
-        .map(|a| a.magnitude())
-        .max()
-        .unwrap()
-        .to_string()
+    fn part1(numbers: &Vec<Number>) -> Answer {
+        Ok(numbers
+            .iter()
+            .cloned()
+            .reduce(|acc, n| acc + n)
+            .unwrap()
+            .simplify()
+            .magnitude()
+            .to_string())
+    }
+
+    fn part2(numbers: &Vec<Number>) -> Answer {
+        Ok(numbers
+            .iter()
+            .permutations(2)
+            .map(|v| v[0].clone() + v[1].clone())
+            .map(|a| a.magnitude())
+            .max()
+            .unwrap()
+            .to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day18;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "3884");
-        assert_eq!(ex2(), "4595");
+        let input = Day18::parse(&fs::read_to_string("inputs/day18.txt").unwrap()).unwrap();
+        assert_eq!(Day18::part1(&input).unwrap(), "3884");
+        assert_eq!(Day18::part2(&input).unwrap(), "4595");
     }
 }
@@ -1,24 +1,29 @@
+use crate::error::{parse_token, ParseError};
+use crate::solution::{Answer, Solution};
 use itertools::Itertools;
 use ndarray::prelude::*;
 use ndarray::{Array, Ix1, Ix2};
 use regex::Regex;
 use std::collections::{HashSet, VecDeque};
-use std::fs;
 use std::ops::Add;
 
-type Vec3D = Array<i32, Ix1>;
-type Mtx = Array<i32, Ix2>;
+pub struct Day19;
 
-type BeaconSignals = HashSet<Vec3D>;
+pub type Vec3D = Array<i32, Ix1>;
+pub type Mtx = Array<i32, Ix2>;
 
+pub type BeaconSignals = HashSet<Vec3D>;
+
+/// Position of a scanner relative to another one, beacons seen by the former are
+/// transformed to the coordinates of the latter by `rotation` followed by `translation`.
 #[derive(Debug)]
-struct RelativePosition {
-    translation: Vec3D,
-    rotation: Mtx,
+pub struct RelativePosition {
+    pub translation: Vec3D,
+    pub rotation: Mtx,
 }
 
 impl RelativePosition {
-    fn manhattan_dist(&self, other: &Self) -> i32 {
+    pub fn manhattan_dist(&self, other: &Self) -> i32 {
         (self.translation[0] - other.translation[0]).abs()
             + (self.translation[1] - other.translation[1]).abs()
             + (self.translation[2] - other.translation[2]).abs()
@@ -36,7 +41,8 @@ impl Add for RelativePosition {
     }
 }
 
-fn all_possible_rotations() -> Vec<Mtx> {
+/// All 48 rotation and reflection matrices along the axes.
+pub fn all_possible_rotations() -> Vec<Mtx> {
     let orders: Vec<Mtx> = Vec::from([
         array![[1, 0, 0], [0, 1, 0], [0, 0, 1]],
         array![[1, 0, 0], [0, 0, 1], [0, 1, 0]],
@@ -63,36 +69,44 @@ fn all_possible_rotations() -> Vec<Mtx> {
         .collect()
 }
 
-fn load_scanner_configurations(s: String) -> Vec<BeaconSignals> {
+fn load_scanner_configurations(s: &str) -> Result<Vec<BeaconSignals>, ParseError> {
     let mut result: Vec<BeaconSignals> = Vec::new();
     let mut current: BeaconSignals = BeaconSignals::new();
-    let mut iter = s.lines().filter(|ln| *ln != "");
+    let iter = s.lines().filter(|ln| !ln.is_empty());
     let header_re = Regex::new(r"^--- scanner \d+ ---$").unwrap();
     let coords_re = Regex::new(r"^(-?\d+),(-?\d+),(-?\d+)$").unwrap();
 
-    while let Some(ln) = iter.next() {
-        if let Some(_) = header_re.captures(ln) {
-            if current.len() > 0 {
+    let mut has_header = false;
+    for ln in iter {
+        if header_re.captures(ln).is_some() {
+            if !current.is_empty() {
                 result.push(current.clone());
             }
             current = BeaconSignals::new();
-        } else if let Some(caps) = coords_re.captures(ln) {
-            current.insert(array![
-                caps.get(1).unwrap().as_str().parse::<i32>().unwrap(),
-                caps.get(2).unwrap().as_str().parse::<i32>().unwrap(),
-                caps.get(3).unwrap().as_str().parse::<i32>().unwrap(),
-            ]);
+            has_header = true;
+        } else if let Some(caps) = coords_re.captures(ln).filter(|_| has_header) {
+            let coord = |k| parse_token(s, caps.get(k).unwrap().as_str(), "coordinate");
+            current.insert(array![coord(1)?, coord(2)?, coord(3)?]);
+        } else if has_header {
+            return Err(ParseError::at(s, ln, "beacon position or scanner header"));
+        } else {
+            return Err(ParseError::at(s, ln, "scanner header"));
         }
     }
 
-    if current.len() > 0 {
+    if !current.is_empty() {
         result.push(current);
     }
 
-    result
+    if result.is_empty() {
+        return Err(ParseError::at_end(s, "beacon position"));
+    }
+
+    Ok(result)
 }
 
-fn relative_scanner_position(
+/// Position of scanner `b` relative to scanner `a`, if they see at least 12 common beacons.
+pub fn relative_scanner_position(
     signals_a: &BeaconSignals,
     signals_b: &BeaconSignals,
     all_rots: &Vec<Mtx>,
@@ -138,11 +152,13 @@ fn change_basis(pos: &RelativePosition, v: Vec3D) -> Vec3D {
 fn change_basis_for_all(pos: &RelativePosition, signals: BeaconSignals) -> BeaconSignals {
     signals
         .iter()
-        .map(|s| change_basis(&pos, s.clone()))
+        .map(|s| change_basis(pos, s.clone()))
         .collect()
 }
 
-fn general_solution(
+/// Transforms the beacons of all scanners to the coordinates of the first one. Returns
+/// the transformed beacons together with the positions of the scanners.
+pub fn general_solution(
     signal_sets: Vec<BeaconSignals>,
 ) -> (Vec<BeaconSignals>, Vec<RelativePosition>) {
     let mut signals: Vec<BeaconSignals> = signal_sets;
@@ -181,38 +197,44 @@ fn general_solution(
     (signals, positions)
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day19.txt").expect("Could not read file");
-    let scanner_confs: Vec<BeaconSignals> = load_scanner_configurations(input_str);
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+    type Input = Vec<BeaconSignals>;
 
-    let (signals, _) = general_solution(scanner_confs);
-    let mut res: BeaconSignals = BeaconSignals::new();
-    for bs in signals {
-        res.extend(bs);
+    fn parse(input: &str) -> Result<Vec<BeaconSignals>, ParseError> {
+        load_scanner_configurations(input)
     }
-    res.len().to_string()
-}
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day19.txt").expect("Could not read file");
-    let scanner_confs: Vec<BeaconSignals> = load_scanner_configurations(input_str);
+    fn part1(scanner_confs: &Vec<BeaconSignals>) -> Answer {
+        let (signals, _) = general_solution(scanner_confs.clone());
+        let mut res: BeaconSignals = BeaconSignals::new();
+        for bs in signals {
+            res.extend(bs);
+        }
+        Ok(res.len().to_string())
+    }
 
-    let (_, positions) = general_solution(scanner_confs);
-    positions
-        .iter()
-        .cartesian_product(positions.iter())
-        .map(|(a, b)| a.manhattan_dist(b))
-        .max()
-        .unwrap()
-        .to_string()
+    fn part2(scanner_confs: &Vec<BeaconSignals>) -> Answer {
+        let (_, positions) = general_solution(scanner_confs.clone());
+        Ok(positions
+            .iter()
+            .cartesian_product(positions.iter())
+            .map(|(a, b)| a.manhattan_dist(b))
+            .max()
+            .unwrap()
+            .to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day19;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "335");
-        assert_eq!(ex2(), "10864");
+        let input = Day19::parse(&fs::read_to_string("inputs/day19.txt").unwrap()).unwrap();
+        assert_eq!(Day19::part1(&input).unwrap(), "335");
+        assert_eq!(Day19::part2(&input).unwrap(), "10864");
     }
 }
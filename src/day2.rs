@@ -1,108 +1,296 @@
-use std::fs;
+use crate::error::{parse_lines, parse_token, ParseError};
+use crate::solution::{Answer, Solution};
+use std::iter;
 
-struct Position {
-    vertical: i32,
-    horizontal: i32,
-    aim: i32,
+pub struct Day2;
+
+/// Position of the submarine, lateral coordinates are only changed by `left` and `right`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    pub vertical: i32,
+    pub horizontal: i32,
+    pub lateral: i32,
+    /// Change of depth per unit of forward movement.
+    pub aim: i32,
+    /// Change of the lateral position per unit of forward movement.
+    pub yaw: i32,
 }
 
-enum Movement {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Movement {
     Forward(u32),
     Down(u32),
     Up(u32),
+    Left(u32),
+    Right(u32),
 }
 
-fn add_1(pos: Position, rhs: Movement) -> Position {
-    match rhs {
-        Movement::Forward(x) => Position {
-            vertical: pos.vertical,
-            horizontal: pos.horizontal + x as i32,
-            aim: pos.aim,
-        },
-        Movement::Up(x) => Position {
-            vertical: pos.vertical - x as i32,
-            horizontal: pos.horizontal,
-            aim: pos.aim,
-        },
-        Movement::Down(x) => Position {
-            vertical: pos.vertical + x as i32,
-            horizontal: pos.horizontal,
-            aim: pos.aim,
-        },
-    }
+/// Semantics of the movement commands.
+pub trait Rules {
+    /// Position after moving from `pos`.
+    fn step(&self, pos: Position, movement: Movement) -> Position;
 }
 
-fn add_2(pos: Position, rhs: Movement) -> Position {
-    match rhs {
-        Movement::Forward(x) => Position {
-            vertical: pos.vertical + pos.aim * x as i32,
-            horizontal: pos.horizontal + x as i32,
-            aim: pos.aim,
-        },
-        Movement::Up(x) => Position {
-            vertical: pos.vertical,
-            horizontal: pos.horizontal,
-            aim: pos.aim - x as i32,
-        },
-        Movement::Down(x) => Position {
-            vertical: pos.vertical,
-            horizontal: pos.horizontal,
-            aim: pos.aim + x as i32,
-        },
+/// Commands move the submarine directly, as in the first part.
+pub struct Plain;
+
+impl Rules for Plain {
+    fn step(&self, pos: Position, movement: Movement) -> Position {
+        match movement {
+            Movement::Forward(x) => Position {
+                horizontal: pos.horizontal + x as i32,
+                ..pos
+            },
+            Movement::Up(x) => Position {
+                vertical: pos.vertical - x as i32,
+                ..pos
+            },
+            Movement::Down(x) => Position {
+                vertical: pos.vertical + x as i32,
+                ..pos
+            },
+            Movement::Left(x) => Position {
+                lateral: pos.lateral - x as i32,
+                ..pos
+            },
+            Movement::Right(x) => Position {
+                lateral: pos.lateral + x as i32,
+                ..pos
+            },
+        }
     }
 }
 
-fn get_movement(line: &str) -> Movement {
-    let v: Vec<&str> = line.split(" ").collect();
-    if let [direction_str, distance_str] = &v[..] {
-        let distance = distance_str.parse::<u32>().unwrap();
-        match *direction_str {
-            "forward" => Movement::Forward(distance),
-            "down" => Movement::Down(distance),
-            "up" => Movement::Up(distance),
-            _ => panic!("Line '{}' did not conform to the expected format", line),
+/// `up` and `down` change the aim, which is followed when moving forward, as in the second
+/// part. `left` and `right` move the submarine directly.
+pub struct Aim;
+
+impl Rules for Aim {
+    fn step(&self, pos: Position, movement: Movement) -> Position {
+        match movement {
+            Movement::Forward(x) => Position {
+                vertical: pos.vertical + pos.aim * x as i32,
+                horizontal: pos.horizontal + x as i32,
+                ..pos
+            },
+            Movement::Up(x) => Position {
+                aim: pos.aim - x as i32,
+                ..pos
+            },
+            Movement::Down(x) => Position {
+                aim: pos.aim + x as i32,
+                ..pos
+            },
+            Movement::Left(_) | Movement::Right(_) => Plain.step(pos, movement),
         }
-    } else {
-        panic!("Line '{}' did not conform to the expected format", line)
     }
 }
 
-fn result_to_string(p: Position) -> String {
-    (p.vertical * p.horizontal).to_string()
+/// Like `Aim`, but `left` and `right` also change the yaw instead of moving directly.
+pub struct Aim3d;
+
+impl Rules for Aim3d {
+    fn step(&self, pos: Position, movement: Movement) -> Position {
+        match movement {
+            Movement::Forward(x) => Position {
+                lateral: pos.lateral + pos.yaw * x as i32,
+                ..Aim.step(pos, movement)
+            },
+            Movement::Up(_) | Movement::Down(_) => Aim.step(pos, movement),
+            Movement::Left(x) => Position {
+                yaw: pos.yaw - x as i32,
+                ..pos
+            },
+            Movement::Right(x) => Position {
+                yaw: pos.yaw + x as i32,
+                ..pos
+            },
+        }
+    }
 }
 
-fn general_solution<F: Fn(Position, Movement) -> Position>(adder: F) -> String {
-    // get starting position
-    let origin = Position {
-        vertical: 0,
-        horizontal: 0,
-        aim: 0,
+fn get_movement(line: &str) -> Result<Movement, ParseError> {
+    let (direction_str, distance_str) = line
+        .split_once(' ')
+        .ok_or_else(|| ParseError::at_end(line, "' ' followed by a distance"))?;
+    let movement: fn(u32) -> Movement = match direction_str {
+        "forward" => Movement::Forward,
+        "down" => Movement::Down,
+        "up" => Movement::Up,
+        "left" => Movement::Left,
+        "right" => Movement::Right,
+        _ => {
+            return Err(ParseError::at(
+                line,
+                direction_str,
+                "'forward', 'down', 'up', 'left' or 'right'",
+            ))
+        }
     };
-    // load file
-    let input: String = fs::read_to_string("inputs/day2.txt").expect("Could not read file");
-    // go through lines one by one
-    let result: Position = input
-        .lines()
-        .map(|line| get_movement(line))
-        .fold(origin, |acc, m| adder(acc, m));
-    // return resulting string
-    result_to_string(result)
+
+    Ok(movement(parse_token(line, distance_str, "distance")?))
 }
 
-pub fn ex1() -> String {
-    general_solution(add_1)
+/// Positions of the submarine starting at the origin, followed by the position after each
+/// of the movements.
+pub fn trace<'a, R: Rules>(
+    movements: &'a [Movement],
+    rules: &'a R,
+) -> impl Iterator<Item = Position> + 'a {
+    let origin = Position::default();
+
+    iter::once(origin).chain(movements.iter().scan(origin, |pos, movement| {
+        *pos = rules.step(*pos, *movement);
+        Some(*pos)
+    }))
 }
 
-pub fn ex2() -> String {
-    general_solution(add_2)
+/// Final position of the submarine after all of the movements.
+pub fn interpret<R: Rules>(movements: &[Movement], rules: &R) -> Position {
+    // go through movements one by one
+    movements.iter().fold(Position::default(), |pos, movement| {
+        rules.step(pos, *movement)
+    })
+}
+
+/// Positions of each of the named traces as CSV, one line per step.
+pub fn trajectory_csv(traces: &[(&str, Vec<Position>)]) -> String {
+    let mut csv = String::from("rules,step,horizontal,depth,aim\n");
+
+    for (name, positions) in traces {
+        for (step, pos) in positions.iter().enumerate() {
+            csv += &format!(
+                "{},{},{},{},{}\n",
+                name, step, pos.horizontal, pos.vertical, pos.aim
+            );
+        }
+    }
+
+    csv
+}
+
+const PANEL_WIDTH: usize = 800;
+const PANEL_HEIGHT: usize = 300;
+
+/// SVG image with a polyline of depth against horizontal distance for each of the named
+/// traces. The traces are drawn in panels below each other, each scaled to fit its panel,
+/// as the depths of different rules may differ by orders of magnitude.
+pub fn trajectory_svg(traces: &[(&str, Vec<Position>)]) -> String {
+    let height = traces.len() * PANEL_HEIGHT;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+        PANEL_WIDTH, height
+    );
+
+    for (k, (name, positions)) in traces.iter().enumerate() {
+        let x = positions.iter().map(|p| p.horizontal);
+        let y = positions.iter().map(|p| p.vertical);
+        let (min_x, max_x) = (x.clone().min().unwrap_or(0), x.max().unwrap_or(0));
+        let (min_y, max_y) = (y.clone().min().unwrap_or(0), y.max().unwrap_or(0));
+        let points: Vec<String> = positions
+            .iter()
+            .map(|p| format!("{},{}", p.horizontal, p.vertical))
+            .collect();
+
+        // the view box maps the positions onto the panel, depth grows downwards as in SVG
+        svg += &format!(
+            "  <text x=\"4\" y=\"{}\">{}</text>\n",
+            k * PANEL_HEIGHT + 16,
+            name
+        );
+        svg += &format!(
+            "  <svg y=\"{}\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\" \
+             preserveAspectRatio=\"none\">\n",
+            k * PANEL_HEIGHT,
+            PANEL_WIDTH,
+            PANEL_HEIGHT,
+            min_x,
+            min_y,
+            (max_x - min_x).max(1),
+            (max_y - min_y).max(1)
+        );
+        svg += &format!(
+            "    <polyline points=\"{}\" fill=\"none\" stroke=\"black\" \
+             vector-effect=\"non-scaling-stroke\"/>\n  </svg>\n",
+            points.join(" ")
+        );
+    }
+
+    svg + "</svg>\n"
+}
+
+fn result_to_string(p: Position) -> String {
+    (p.vertical * p.horizontal).to_string()
+}
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+    type Input = Vec<Movement>;
+
+    fn parse(input: &str) -> Result<Vec<Movement>, ParseError> {
+        parse_lines(input, get_movement)
+    }
+
+    fn part1(movements: &Vec<Movement>) -> Answer {
+        Ok(result_to_string(interpret(movements, &Plain)))
+    }
+
+    fn part2(movements: &Vec<Movement>) -> Answer {
+        Ok(result_to_string(interpret(movements, &Aim)))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::{
+        interpret, trace, trajectory_csv, trajectory_svg, Aim, Aim3d, Day2, Plain, Position,
+    };
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "1990000");
-        assert_eq!(ex2(), "1975421260");
+        let input = Day2::parse(&fs::read_to_string("inputs/day2.txt").unwrap()).unwrap();
+        assert_eq!(Day2::part1(&input).unwrap(), "1990000");
+        assert_eq!(Day2::part2(&input).unwrap(), "1975421260");
+    }
+
+    #[test]
+    fn test_rules() {
+        let movements = Day2::parse("down 5\nright 2\nforward 3\nleft 1\nforward 2\n").unwrap();
+
+        let plain = interpret(&movements, &Plain);
+        assert_eq!((plain.horizontal, plain.vertical, plain.lateral), (5, 5, 1));
+        let aim = interpret(&movements, &Aim);
+        assert_eq!((aim.horizontal, aim.vertical, aim.lateral), (5, 25, 1));
+        let aim_3d = interpret(&movements, &Aim3d);
+        assert_eq!(
+            (aim_3d.horizontal, aim_3d.vertical, aim_3d.lateral),
+            (5, 25, 8)
+        );
+
+        let positions: Vec<Position> = trace(&movements, &Aim3d).collect();
+        assert_eq!(positions.len(), movements.len() + 1);
+        assert_eq!(positions[0], Position::default());
+        assert_eq!(positions[3].lateral, 6);
+        assert_eq!(*positions.last().unwrap(), aim_3d);
+    }
+
+    #[test]
+    fn test_trajectory_export() {
+        let movements = Day2::parse("forward 5\ndown 5\nforward 8\n").unwrap();
+        let traces = [
+            ("plain", trace(&movements, &Plain).collect()),
+            ("aim", trace(&movements, &Aim).collect()),
+        ];
+
+        let csv = trajectory_csv(&traces);
+        assert_eq!(csv.lines().count(), 9);
+        assert_eq!(csv.lines().nth(4).unwrap(), "plain,3,13,5,0");
+        assert_eq!(csv.lines().last().unwrap(), "aim,3,13,40,5");
+
+        let svg = trajectory_svg(&traces);
+        assert!(svg.contains("viewBox=\"0 0 13 40\""));
+        assert!(svg.contains("points=\"0,0 5,0 5,0 13,40\""));
+        assert_eq!(svg.matches("<polyline").count(), 2);
     }
 }
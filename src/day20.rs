@@ -1,26 +1,41 @@
+use crate::error::ParseError;
+use crate::solution::{Answer, Solution};
 use std::collections::HashSet;
-use std::fs;
+
+pub struct Day20;
 
 type Positions = HashSet<(i32, i32)>;
-type EnhancementMap = [bool; 512];
-type Pixels = HashSet<(i32, i32, bool)>;
+pub type EnhancementMap = [bool; 512];
+pub type Pixels = HashSet<(i32, i32, bool)>;
 
-struct Image {
-    pixels: Pixels,
-    is_background_white: bool,
+#[derive(Clone)]
+pub struct Image {
+    pub pixels: Pixels,
+    pub is_background_white: bool,
 }
 
-fn retrieve_configuration(s: String) -> (EnhancementMap, Image) {
-    let v: Vec<&str> = s.split("\n\n").collect();
+fn retrieve_configuration(s: &str) -> Result<(EnhancementMap, Image), ParseError> {
+    let (map_str, image_str) = s
+        .split_once("\n\n")
+        .ok_or_else(|| ParseError::at_end(s, "empty line followed by the image"))?;
     let mut map: EnhancementMap = [false; 512];
-    for (k, c) in v[0].chars().filter(|c| *c != '\n').enumerate() {
-        map[k] = c == '#';
+    let mut map_len = 0;
+    for (k, c) in map_str.char_indices().filter(|(_, c)| *c != '\n') {
+        if (c != '#' && c != '.') || map_len == map.len() {
+            let token = &map_str[k..k + c.len_utf8()];
+            return Err(ParseError::at(s, token, "512 '#' or '.' symbols"));
+        }
+        map[map_len] = c == '#';
+        map_len += 1;
+    }
+    if map_len < map.len() {
+        return Err(ParseError::at_end(map_str, "512 '#' or '.' symbols"));
     }
 
     let mut x: i32 = 0;
     let mut y: i32 = 0;
     let mut pixels: Pixels = Pixels::new();
-    for c in v[1].chars() {
+    for (k, c) in image_str.char_indices() {
         match c {
             '\n' => {
                 x = 0;
@@ -35,18 +50,19 @@ fn retrieve_configuration(s: String) -> (EnhancementMap, Image) {
                 x += 1;
             }
             _ => {
-                panic!("Unexpected symbol");
+                let token = &image_str[k..k + c.len_utf8()];
+                return Err(ParseError::at(s, token, "'#' or '.'"));
             }
         }
     }
 
-    (
+    Ok((
         map,
         Image {
-            pixels: pixels,
+            pixels,
             is_background_white: false,
         },
-    )
+    ))
 }
 
 fn is_white((y, x): (i32, i32), img: &Image, map: &EnhancementMap) -> bool {
@@ -64,9 +80,9 @@ fn is_white((y, x): (i32, i32), img: &Image, map: &EnhancementMap) -> bool {
         (1, 1),
     ] {
         k = 2 * k
-            + if img.pixels.get(&(y + inc_y, x + inc_x, true)).is_some() {
+            + if img.pixels.contains(&(y + inc_y, x + inc_x, true)) {
                 1
-            } else if img.pixels.get(&(y + inc_y, x + inc_x, false)).is_some() {
+            } else if img.pixels.contains(&(y + inc_y, x + inc_x, false)) {
                 0
             } else if img.is_background_white {
                 1
@@ -105,14 +121,13 @@ fn step(img: Image, map: &EnhancementMap) -> Image {
     for p in img
         .pixels
         .iter()
-        .map(|(y, x, _)| surrounding_candidates((*y, *x)))
-        .flatten()
+        .flat_map(|(y, x, _)| surrounding_candidates((*y, *x)))
     {
         candidates.insert(p);
     }
 
     for (y, x) in candidates {
-        new_pixels.insert((y, x, is_white((y, x), &img, &map)));
+        new_pixels.insert((y, x, is_white((y, x), &img, map)));
     }
 
     Image {
@@ -125,12 +140,11 @@ fn step(img: Image, map: &EnhancementMap) -> Image {
     }
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day20.txt").expect("Could not read file");
-    let (map, mut image) = retrieve_configuration(input_str);
+fn general_solution((map, image): &(EnhancementMap, Image), step_count: u16) -> String {
+    let mut image: Image = image.clone();
 
-    for _ in 0..2 {
-        image = step(image, &map);
+    for _ in 0..step_count {
+        image = step(image, map);
     }
 
     image
@@ -141,28 +155,32 @@ pub fn ex1() -> String {
         .to_string()
 }
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day20.txt").expect("Could not read file");
-    let (map, mut image) = retrieve_configuration(input_str);
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+    type Input = (EnhancementMap, Image);
 
-    for _ in 0..50 {
-        image = step(image, &map);
+    fn parse(input: &str) -> Result<(EnhancementMap, Image), ParseError> {
+        retrieve_configuration(input)
     }
 
-    image
-        .pixels
-        .iter()
-        .filter(|(_, _, c)| *c)
-        .count()
-        .to_string()
+    fn part1(configuration: &(EnhancementMap, Image)) -> Answer {
+        Ok(general_solution(configuration, 2))
+    }
+
+    fn part2(configuration: &(EnhancementMap, Image)) -> Answer {
+        Ok(general_solution(configuration, 50))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day20;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "5218");
-        assert_eq!(ex2(), "15527");
+        let input = Day20::parse(&fs::read_to_string("inputs/day20.txt").unwrap()).unwrap();
+        assert_eq!(Day20::part1(&input).unwrap(), "5218");
+        assert_eq!(Day20::part2(&input).unwrap(), "15527");
     }
 }
@@ -1,7 +1,9 @@
-use regex::Regex;
+use crate::error::{parse_token, ParseError};
+use crate::solution::{Answer, Solution};
 use std::cmp::Ordering;
 use std::collections::{BTreeSet, HashMap};
-use std::fs;
+
+pub struct Day21;
 
 const EX1_LIMIT: u16 = 1000;
 const EX2_LIMIT: u16 = 21;
@@ -24,7 +26,7 @@ impl Game {
             ((3 * self.step_count + 2) % 100) + 1,
         );
 
-        if self.step_count % 2 == 0 {
+        if self.step_count.is_multiple_of(2) {
             self.pos1 = (((self.pos1 as u16 + d1 + d2 + d3 - 1) % MAX_POSITION) + 1) as u8;
             self.score1 += self.pos1 as u16;
         } else {
@@ -47,10 +49,8 @@ impl Game {
     fn over(&self, limit: u16) -> bool {
         if self.score1 >= limit {
             true
-        } else if self.score2 >= limit {
-            true
         } else {
-            false
+            self.score2 >= limit
         }
     }
 }
@@ -87,22 +87,32 @@ impl Ord for Game {
     }
 }
 
-fn load_configuration(s: String) -> (u8, u8) {
-    let re = Regex::new(r"Player 1 starting position: (\d+)\nPlayer 2 starting position: (\d+)")
-        .unwrap();
-    let caps = re.captures(&s).unwrap();
-    (
-        caps.get(1).unwrap().as_str().parse::<u8>().unwrap(),
-        caps.get(2).unwrap().as_str().parse::<u8>().unwrap(),
-    )
+fn load_position(s: &str, line: Option<&str>, player: u8) -> Result<u8, ParseError> {
+    let prefix = format!("Player {} starting position: ", player);
+    let expected = format!("'{}N'", prefix);
+    let line = line.ok_or_else(|| ParseError::at_end(s, &expected))?;
+    let token = line
+        .strip_prefix(&prefix)
+        .ok_or_else(|| ParseError::at(s, line, &expected))?;
+
+    match parse_token(s, token, "position between 1 and 10")? {
+        pos @ 1..=10 => Ok(pos),
+        _ => Err(ParseError::at(s, token, "position between 1 and 10")),
+    }
+}
+
+fn load_configuration(s: &str) -> Result<(u8, u8), ParseError> {
+    let mut lines = s.lines();
+    Ok((
+        load_position(s, lines.next(), 1)?,
+        load_position(s, lines.next(), 2)?,
+    ))
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day21.txt").expect("Could not read file");
-    let (pos1, pos2) = load_configuration(input_str);
+fn deterministic_game((pos1, pos2): (u8, u8)) -> String {
     let mut game = Game {
-        pos1: pos1,
-        pos2: pos2,
+        pos1,
+        pos2,
         score1: 0,
         score2: 0,
         step_count: 0,
@@ -114,9 +124,7 @@ pub fn ex1() -> String {
     game.ex1_score().to_string()
 }
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day21.txt").expect("Could not read file");
-    let (pos1, pos2) = load_configuration(input_str);
+fn dirac_game((pos1, pos2): (u8, u8)) -> String {
     let mut wins1: i64 = 0;
     let mut wins2: i64 = 0;
     let start_game = Game {
@@ -188,12 +196,32 @@ pub fn ex2() -> String {
     wins1.max(wins2).to_string()
 }
 
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+    type Input = (u8, u8);
+
+    fn parse(input: &str) -> Result<(u8, u8), ParseError> {
+        load_configuration(input)
+    }
+
+    fn part1(positions: &(u8, u8)) -> Answer {
+        Ok(deterministic_game(*positions))
+    }
+
+    fn part2(positions: &(u8, u8)) -> Answer {
+        Ok(dirac_game(*positions))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day21;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "998088");
-        assert_eq!(ex2(), "306621346123766");
+        let input = Day21::parse(&fs::read_to_string("inputs/day21.txt").unwrap()).unwrap();
+        assert_eq!(Day21::part1(&input).unwrap(), "998088");
+        assert_eq!(Day21::part2(&input).unwrap(), "306621346123766");
     }
 }
@@ -1,25 +1,29 @@
+use crate::error::{parse_token, ParseError};
+use crate::solution::{Answer, Solution};
 use regex::Regex;
 use std::collections::VecDeque;
-use std::fs;
 
+pub struct Day22;
+
+/// Cuboid of the points `from <= p < to` along each axis.
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Clone)]
-struct Cuboid {
-    x_from: i32,
-    x_to: i32,
-    y_from: i32,
-    y_to: i32,
-    z_from: i32,
-    z_to: i32,
+pub struct Cuboid {
+    pub x_from: i32,
+    pub x_to: i32,
+    pub y_from: i32,
+    pub y_to: i32,
+    pub z_from: i32,
+    pub z_to: i32,
 }
 
-#[derive(Debug)]
-struct RebootStep {
-    cuboid: Cuboid,
-    on: bool,
+#[derive(Debug, Clone)]
+pub struct RebootStep {
+    pub cuboid: Cuboid,
+    pub on: bool,
 }
 
 impl Cuboid {
-    fn intersection(&self, other: &Cuboid) -> Option<Cuboid> {
+    pub fn intersection(&self, other: &Cuboid) -> Option<Cuboid> {
         let no_intersection_x = self.x_to <= other.x_from || other.x_to <= self.x_from;
         let no_intersection_y = self.y_to <= other.y_from || other.y_to <= self.y_from;
         let no_intersection_z = self.z_to <= other.z_from || other.z_to <= self.z_from;
@@ -38,7 +42,8 @@ impl Cuboid {
         })
     }
 
-    fn dissasemble(&self, subpiece: &Cuboid) -> Vec<Cuboid> {
+    /// Splits the cuboid into up to 27 pieces along the faces of `subpiece`.
+    pub fn dissasemble(&self, subpiece: &Cuboid) -> Vec<Cuboid> {
         let x_points = [self.x_from, subpiece.x_from, subpiece.x_to, self.x_to];
         let y_points = [self.y_from, subpiece.y_from, subpiece.y_to, self.y_to];
         let z_points = [self.z_from, subpiece.z_from, subpiece.z_to, self.z_to];
@@ -74,14 +79,15 @@ impl Cuboid {
         ret
     }
 
-    fn volume(&self) -> u128 {
+    pub fn volume(&self) -> u128 {
         let x = (self.x_to - self.x_from) as u128;
         let y = (self.y_to - self.y_from) as u128;
         let z = (self.z_to - self.z_from) as u128;
         x * y * z
     }
 
-    fn is_limited(&self) -> bool {
+    /// Checks whether the cuboid lies within the initialization region.
+    pub fn is_limited(&self) -> bool {
         -50 <= self.x_from
             && self.x_from <= 50
             && -49 <= self.x_to
@@ -97,43 +103,43 @@ impl Cuboid {
     }
 }
 
-fn load_reboot_steps(s: String) -> VecDeque<RebootStep> {
+fn load_reboot_steps(s: &str) -> Result<VecDeque<RebootStep>, ParseError> {
     let mut ret = VecDeque::new();
     let re =
-        Regex::new(r"(on|off) x=(-?\d+)..(-?\d+),y=(-?\d+)..(-?\d+),z=(-?\d+)..(-?\d+)").unwrap();
+        Regex::new(r"^(on|off) x=(-?\d+)\.\.(-?\d+),y=(-?\d+)\.\.(-?\d+),z=(-?\d+)\.\.(-?\d+)$")
+            .unwrap();
 
     let mut caps;
     for ln in s.lines() {
-        caps = re.captures(&ln).unwrap();
+        caps = re.captures(ln).ok_or_else(|| {
+            ParseError::at(s, ln, "reboot step in the form 'on x=A..B,y=C..D,z=E..F'")
+        })?;
+        let coord = |k| parse_token::<i32>(s, caps.get(k).unwrap().as_str(), "coordinate");
         ret.push_back(RebootStep {
             on: caps.get(1).unwrap().as_str() == "on",
             cuboid: Cuboid {
-                x_from: caps.get(2).unwrap().as_str().parse::<i32>().unwrap(),
-                x_to: caps.get(3).unwrap().as_str().parse::<i32>().unwrap() + 1,
-                y_from: caps.get(4).unwrap().as_str().parse::<i32>().unwrap(),
-                y_to: caps.get(5).unwrap().as_str().parse::<i32>().unwrap() + 1,
-                z_from: caps.get(6).unwrap().as_str().parse::<i32>().unwrap(),
-                z_to: caps.get(7).unwrap().as_str().parse::<i32>().unwrap() + 1,
+                x_from: coord(2)?,
+                x_to: coord(3)? + 1,
+                y_from: coord(4)?,
+                y_to: coord(5)? + 1,
+                z_from: coord(6)?,
+                z_to: coord(7)? + 1,
             },
         });
     }
 
-    ret
+    Ok(ret)
 }
 
-fn general_solution(mut steps: VecDeque<RebootStep>) -> u128 {
+/// Number of points turned on after performing all of the steps.
+pub fn general_solution(mut steps: VecDeque<RebootStep>) -> u128 {
     let mut space: Vec<Cuboid> = Vec::new();
     let mut intersecting_cuboid_idx: Option<usize>;
 
     while let Some(reboot_step) = steps.pop_front() {
-        intersecting_cuboid_idx = None;
-
-        for cuboid_idx in 0..space.len() {
-            if let Some(_) = reboot_step.cuboid.intersection(&space[cuboid_idx]) {
-                intersecting_cuboid_idx = Some(cuboid_idx);
-                break;
-            }
-        }
+        intersecting_cuboid_idx = space
+            .iter()
+            .position(|cuboid| reboot_step.cuboid.intersection(cuboid).is_some());
 
         if let Some(cuboid_idx) = intersecting_cuboid_idx {
             let cuboid: Cuboid = space[cuboid_idx].clone();
@@ -179,29 +185,38 @@ fn general_solution(mut steps: VecDeque<RebootStep>) -> u128 {
     space.into_iter().map(|c| c.volume()).sum()
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day22.txt").expect("Could not read file");
-    let steps = load_reboot_steps(input_str)
-        .into_iter()
-        .filter(|step| step.cuboid.is_limited())
-        .collect();
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+    type Input = VecDeque<RebootStep>;
 
-    general_solution(steps).to_string()
-}
+    fn parse(input: &str) -> Result<VecDeque<RebootStep>, ParseError> {
+        load_reboot_steps(input)
+    }
+
+    fn part1(steps: &VecDeque<RebootStep>) -> Answer {
+        let steps = steps
+            .iter()
+            .filter(|step| step.cuboid.is_limited())
+            .cloned()
+            .collect();
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day22.txt").expect("Could not read file");
-    let steps = load_reboot_steps(input_str);
+        Ok(general_solution(steps).to_string())
+    }
 
-    general_solution(steps).to_string()
+    fn part2(steps: &VecDeque<RebootStep>) -> Answer {
+        Ok(general_solution(steps.clone()).to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day22;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "615869");
-        assert_eq!(ex2(), "1323862415207825");
+        let input = Day22::parse(&fs::read_to_string("inputs/day22.txt").unwrap()).unwrap();
+        assert_eq!(Day22::part1(&input).unwrap(), "615869");
+        assert_eq!(Day22::part2(&input).unwrap(), "1323862415207825");
     }
 }
@@ -1,7 +1,13 @@
+use crate::error::ParseError;
+use crate::solution::{Answer, Solution};
 use regex::Regex;
 use std::cmp::Ordering;
 use std::collections::{BTreeSet, BinaryHeap, HashMap};
-use std::fs;
+
+pub struct Day23;
+
+/// Amphipods in the side rooms listed row by row, starting with the row next to the hallway.
+pub type Burrow = [[char; 4]; 4];
 
 #[derive(Debug, Eq, PartialEq)]
 struct State {
@@ -9,26 +15,43 @@ struct State {
     positions: BTreeSet<(char, u32, u32)>,
 }
 
-impl State {
-    fn new(s: String, simplified: bool) -> State {
-        let mut re_str: String = String::from("##([A-D])#([A-D])#([A-D])#([A-D])###\n");
-        for _ in 0..3 {
-            re_str.push_str("  #([A-D])#([A-D])#([A-D])#([A-D])#\n");
+fn load_burrow(s: &str) -> Result<Burrow, ParseError> {
+    let mut re_str: String = String::from("##([A-D])#([A-D])#([A-D])#([A-D])###\n");
+    for _ in 0..3 {
+        re_str.push_str("  #([A-D])#([A-D])#([A-D])#([A-D])#\n");
+    }
+    let re = Regex::new(&re_str).unwrap();
+    let caps = re
+        .captures(s)
+        .ok_or_else(|| ParseError::at(s, s.trim_end(), "burrow with four rows of amphipods"))?;
+
+    let mut burrow: Burrow = [['.'; 4]; 4];
+    for (row, amphipods) in burrow.iter_mut().enumerate() {
+        for (col, c) in amphipods.iter_mut().enumerate() {
+            *c = caps
+                .get(4 * row + col + 1)
+                .unwrap()
+                .as_str()
+                .parse::<char>()
+                .unwrap();
         }
-        let re = Regex::new(&re_str).unwrap();
-        let caps = re.captures(&s).unwrap();
+    }
 
+    Ok(burrow)
+}
+
+impl State {
+    fn new(burrow: &Burrow, simplified: bool) -> State {
         let mut y_counter = 0;
         let mut set: BTreeSet<(char, u32, u32)> = BTreeSet::new();
-        for row in 0..4 {
+        for (row, amphipods) in burrow.iter().enumerate() {
             if simplified && (row == 1 || row == 2) {
                 continue;
             }
 
-            set.insert((caps.get(4 * row as usize + 1).unwrap().as_str().parse::<char>().unwrap(), y_counter + 2, 3));
-            set.insert((caps.get(4 * row as usize + 2).unwrap().as_str().parse::<char>().unwrap(), y_counter + 2, 5));
-            set.insert((caps.get(4 * row as usize + 3).unwrap().as_str().parse::<char>().unwrap(), y_counter + 2, 7));
-            set.insert((caps.get(4 * row as usize + 4).unwrap().as_str().parse::<char>().unwrap(), y_counter + 2, 9));
+            for (col, c) in amphipods.iter().enumerate() {
+                set.insert((*c, y_counter + 2, 3 + 2 * col as u32));
+            }
 
             y_counter += 1;
         }
@@ -44,7 +67,7 @@ impl State {
     }
 
     fn amphipod_cost(c: char) -> u32 {
-        (10 as u32).pow(c as u32 - 'A' as u32)
+        10_u32.pow(c as u32 - 'A' as u32)
     }
 
     fn is_end_state(&self) -> bool {
@@ -62,7 +85,7 @@ impl State {
         true
     }
 
-    fn accessible_states(&self, all_positions: &Vec<(u32, u32)>) -> Vec<State> {
+    fn accessible_states(&self, all_positions: &Vec<(u32, u32)>, pruned: bool) -> Vec<State> {
         let mut ret: Vec<State> = Vec::new();
         let mut new_positions = self.positions.clone();
         let mut c;
@@ -76,6 +99,9 @@ impl State {
                 if !State::is_accessible(*old_pos, (*y_new, *x_new), &self.positions) {
                     continue;
                 }
+                if pruned && !State::is_useful(*old_pos, (*y_new, *x_new), &self.positions) {
+                    continue;
+                }
 
                 new_positions.remove(old_pos);
                 new_positions.insert((c, *y_new, *x_new));                
@@ -192,16 +218,42 @@ impl State {
         true
     }
 
-    fn cost((c, y_old, x_old): (char, u32, u32), (y_new, x_new): (u32, u32)) -> u32 {
-        let k: u32;
+    /// Checks whether an accessible move can be part of an optimal solution, moves that only
+    /// lead to more expensive detours are pruned from the search.
+    fn is_useful(
+        (c, y_old, x_old): (char, u32, u32),
+        (y_new, x_new): (u32, u32),
+        positions: &BTreeSet<(char, u32, u32)>,
+    ) -> bool {
+        // Rooms are filled from the bottom, so no free space is left below the new position.
+        let depth = 1 + positions.len() as u32 / 4;
+        if y_new >= 2 {
+            for y_temp in y_new + 1..=depth {
+                if !positions
+                    .iter()
+                    .any(|(_, y, x)| *y == y_temp && *x == x_new)
+                {
+                    return false;
+                }
+            }
+        }
 
-        if y_old >= 2 && y_new >= 2 {
+        // An amphipod in its own chamber with only its own kind below it never has to move.
+        !(y_old >= 2
+            && x_old == State::amphipod_line(c)
+            && positions
+                .iter()
+                .all(|(c_other, y, x)| *x != x_old || *y <= y_old || *c_other == c))
+    }
+
+    fn cost((c, y_old, x_old): (char, u32, u32), (y_new, x_new): (u32, u32)) -> u32 {
+        let k: u32 = if y_old >= 2 && y_new >= 2 {
             // Room-to-room movement
-            k = y_old.abs_diff(1) + y_new.abs_diff(1) + x_old.abs_diff(x_new);
+            y_old.abs_diff(1) + y_new.abs_diff(1) + x_old.abs_diff(x_new)
         } else {
             // Room-to-hall or hall-to-room movement
-            k = y_old.abs_diff(y_new) + x_old.abs_diff(x_new);
-        }
+            y_old.abs_diff(y_new) + x_old.abs_diff(x_new)
+        };
 
         // The cost depends on the distance and the type of the amphipod.
         k * State::amphipod_cost(c)
@@ -224,7 +276,7 @@ impl PartialOrd for State {
     }
 }
 
-fn general_solution(input_str: String, simplified: bool) -> u32 {
+fn general_solution(burrow: &Burrow, simplified: bool, pruned: bool) -> u32 {
     let mut all_positions: Vec<(u32, u32)> = Vec::from([(1, 1), (1, 2), (1, 3), (1, 4), (1, 5), (1, 6),
                                                         (1, 7), (1, 8), (1, 9), (1, 10), (1, 11),
                                                         (2, 3), (2, 5), (2, 7), (2, 9),
@@ -237,7 +289,7 @@ fn general_solution(input_str: String, simplified: bool) -> u32 {
 
     // Initialize data structures needed for Dijkstra.
     let mut best_costs: HashMap<BTreeSet<(char, u32, u32)>, u32> = HashMap::new();
-    let mut heap: BinaryHeap<State> = BinaryHeap::from([State::new(input_str, simplified)]);
+    let mut heap: BinaryHeap<State> = BinaryHeap::from([State::new(burrow, simplified)]);
 
     while let Some(s) = heap.pop() {
         // Return cost if a solution was found.
@@ -251,7 +303,7 @@ fn general_solution(input_str: String, simplified: bool) -> u32 {
             }
         }
         // Extend heap with newly accessible states.
-        for accessible_s in s.accessible_states(&all_positions) {
+        for accessible_s in s.accessible_states(&all_positions, pruned) {
             match best_costs.get(&accessible_s.positions) {
                 Some(current_cost) if accessible_s.cost >= *current_cost => {}
                 _ => {
@@ -265,22 +317,41 @@ fn general_solution(input_str: String, simplified: bool) -> u32 {
     panic!("No solution was found.")    
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day23.txt").expect("Could not read file");
-    general_solution(input_str, true).to_string()
-}
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+    type Input = Burrow;
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day23.txt").expect("Could not read file");
-    general_solution(input_str, false).to_string()
+    fn parse(input: &str) -> Result<Burrow, ParseError> {
+        load_burrow(input)
+    }
+
+    fn part1(burrow: &Burrow) -> Answer {
+        Ok(general_solution(burrow, true, true).to_string())
+    }
+
+    fn part2(burrow: &Burrow) -> Answer {
+        Ok(general_solution(burrow, false, true).to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::{general_solution, Day23};
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "14415");
-        assert_eq!(ex2(), "41121");
+        let input = Day23::parse(&fs::read_to_string("inputs/day23.txt").unwrap()).unwrap();
+        assert_eq!(Day23::part1(&input).unwrap(), "14415");
+        assert_eq!(Day23::part2(&input).unwrap(), "41121");
+    }
+
+    #[test]
+    fn test_pruned_search() {
+        let input = Day23::parse(&fs::read_to_string("tests/examples/day23.txt").unwrap()).unwrap();
+        assert_eq!(general_solution(&input, true, false), 12521);
+        assert_eq!(general_solution(&input, true, true), 12521);
+        // Without pruning, the unfolded burrow of the second part exhausts the memory.
+        assert_eq!(general_solution(&input, false, true), 44169);
     }
 }
@@ -1,69 +1,76 @@
+use crate::error::{parse_token, ParseError};
+use crate::solution::{Answer, Solution};
 use regex::Regex;
 use std::collections::HashMap;
-use std::fs;
 
+pub struct Day24;
+
+/// Constants of a single `inp w` block of the MONAD program, which differ between the blocks.
 #[derive(Debug)]
-struct IterationParams {
-    add: i32,
-    add2: i32,
-    div: i32,
+pub struct IterationParams {
+    pub add: i32,
+    pub add2: i32,
+    pub div: i32,
+}
+
+fn load_param(s: &str, re: &Regex, ln: Option<&str>) -> Result<i32, ParseError> {
+    let ln = ln.ok_or_else(|| ParseError::at_end(s, "'add' or 'div' instruction"))?;
+    let caps = re
+        .captures(ln)
+        .ok_or_else(|| ParseError::at(s, ln, "'add' or 'div' instruction with a constant"))?;
+
+    parse_token(s, caps.get(2).unwrap().as_str(), "constant")
 }
 
-fn load_configurations(s: String) -> Vec<IterationParams> {
+fn load_configurations(s: &str) -> Result<Vec<IterationParams>, ParseError> {
     let mut ret = Vec::new();
-    let mut lines = s.lines().clone();
+    let mut lines = s.lines();
     let mut add;
     let mut add2;
     let mut div;
-    let mut caps;
-    let mut ln;
-    let re = Regex::new(r"(add|div) [xyz] (-?\d+)").unwrap();
+    let re = Regex::new(r"^(add|div) [xyz] (-?\d+)$").unwrap();
 
     // Process individual instruction sequences starting with "inp w".
     // Each iteration has three variable values. Each struct will contain
     // this trinity.
-    while let Some("inp w") = lines.next() {
-        lines.nth(2);
-
-        ln = lines.next().unwrap();
-        caps = re.captures(&ln).unwrap();
-        div = caps.get(2).unwrap().as_str().parse::<i32>().unwrap();
-
-        ln = lines.next().unwrap();
-        caps = re.captures(&ln).unwrap();
-        add = caps.get(2).unwrap().as_str().parse::<i32>().unwrap();
-
-        lines.nth(8);
+    while let Some(ln) = lines.next() {
+        if ln != "inp w" {
+            return Err(ParseError::at(s, ln, "'inp w'"));
+        }
 
-        ln = lines.next().unwrap();
-        caps = re.captures(&ln).unwrap();
-        add2 = caps.get(2).unwrap().as_str().parse::<i32>().unwrap();
+        div = load_param(s, &re, lines.nth(3))?;
+        add = load_param(s, &re, lines.next())?;
+        add2 = load_param(s, &re, lines.nth(9))?;
 
         lines.nth(1);
 
         ret.push(IterationParams { add, add2, div });
     }
 
-    ret
+    if ret.is_empty() {
+        return Err(ParseError::at_end(s, "'inp w'"));
+    }
+
+    Ok(ret)
 }
 
-fn iteration(w: i32, z: i32, params: &IterationParams) -> i32 {
+fn iteration(w: i64, z: i64, params: &IterationParams) -> i64 {
     // This is a function extracted from the input instructions.
     // Only changing parameters are separated into the `IterationParams`
     // as everything else stays the same.
-    if w == (z % 26) + params.add {
-        z / params.div
+    if w == (z % 26) + params.add as i64 {
+        z / params.div as i64
     } else {
-        26 * z / params.div + w + params.add2
+        26 * z / params.div as i64 + w + params.add2 as i64
     }
 }
 
 fn general_solution(
-    param_sequence: Vec<IterationParams>,
+    param_sequence: &[IterationParams],
     selection_fn: fn(u128, u128) -> u128,
 ) -> u128 {
-    let mut map: HashMap<i32, u128> = HashMap::from([(0, 0)]);
-    let mut temp_map: HashMap<i32, u128>;
+    let mut map: HashMap<i64, u128> = HashMap::from([(0, 0)]);
+    let mut temp_map: HashMap<i64, u128>;
     let mut contraction_counts: [u8; 14] = [0; 14];
 
     // Precompute the number of contractions that will be performed in the future for each iteration.
@@ -82,12 +89,16 @@ fn general_solution(
         for (z, ext) in map.iter() {
             // As each iteration the value can be divided by 26 or not at all,
             // some values are too large or too small to realistically reach 0. These can be pruned.
-            let r = (26 as u32).pow(contraction_counts[k] as u32) as i64;
-            if *z as i64 > r {
+            let r = 26_i64
+                .checked_pow(contraction_counts[k] as u32)
+                .unwrap_or(i64::MAX);
+            if *z > r {
                 continue;
             }
-            let r = -((26 as u32).pow(k as u32 - contraction_counts[k] as u32) as i64);
-            if (*z as i64) < r {
+            let r = -(26_i64
+                .checked_pow((k as u32).saturating_sub(contraction_counts[k] as u32))
+                .unwrap_or(i64::MAX));
+            if *z < r {
                 continue;
             }
 
@@ -98,12 +109,12 @@ fn general_solution(
                 // Insert into the map if the new value of register z is not there yet or
                 // if the aggregated value can be improved.
                 let v_new = 10 * ext + w as u128;
-                if temp_map.contains_key(&z_next) {
+                if let std::collections::hash_map::Entry::Vacant(e) = temp_map.entry(z_next) {
+                    e.insert(v_new);
+                } else {
                     temp_map
                         .entry(z_next)
                         .and_modify(|v| *v = selection_fn(*v, v_new));
-                } else {
-                    temp_map.insert(z_next, v_new);
                 }
             }
         }
@@ -114,26 +125,32 @@ fn general_solution(
     *map.get(&0).unwrap()
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day24.txt").expect("Could not read file");
-    let param_sequence = load_configurations(input_str);
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+    type Input = Vec<IterationParams>;
 
-    general_solution(param_sequence, |a, b| a.max(b)).to_string()
-}
+    fn parse(input: &str) -> Result<Vec<IterationParams>, ParseError> {
+        load_configurations(input)
+    }
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day24.txt").expect("Could not read file");
-    let param_sequence = load_configurations(input_str);
+    fn part1(param_sequence: &Vec<IterationParams>) -> Answer {
+        Ok(general_solution(param_sequence, |a, b| a.max(b)).to_string())
+    }
 
-    general_solution(param_sequence, |a, b| a.min(b)).to_string()
+    fn part2(param_sequence: &Vec<IterationParams>) -> Answer {
+        Ok(general_solution(param_sequence, |a, b| a.min(b)).to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day24;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "99893999291967");
-        assert_eq!(ex2(), "34171911181211");
+        let input = Day24::parse(&fs::read_to_string("inputs/day24.txt").unwrap()).unwrap();
+        assert_eq!(Day24::part1(&input).unwrap(), "99893999291967");
+        assert_eq!(Day24::part2(&input).unwrap(), "34171911181211");
     }
 }
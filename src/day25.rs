@@ -1,18 +1,21 @@
+use crate::error::ParseError;
+use crate::solution::{Answer, Solution};
 use std::collections::BTreeSet;
-use std::fs;
 
-type Position = (u32, u32);
+pub struct Day25;
 
-#[derive(Debug, Eq, PartialEq)]
-struct State {
-    board_width: u32,
-    board_height: u32,
-    east_herd: BTreeSet<Position>,
-    south_herd: BTreeSet<Position>,
+pub type Position = (u32, u32);
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct State {
+    pub board_width: u32,
+    pub board_height: u32,
+    pub east_herd: BTreeSet<Position>,
+    pub south_herd: BTreeSet<Position>,
 }
 
 impl State {
-    fn from(s: String) -> State {
+    fn from(s: &str) -> Result<State, ParseError> {
         let mut y: u32 = 0;
         let mut x: u32 = 0;
         let mut east_herd: BTreeSet<Position> = BTreeSet::new();
@@ -21,12 +24,15 @@ impl State {
         for ln in s.lines() {
             x = 0;
 
-            for c in ln.chars() {
+            for (k, c) in ln.char_indices() {
                 match c {
                     '>' => east_herd.insert((y, x)),
                     'v' => south_herd.insert((y, x)),
                     '.' => false,
-                    _ => panic!("Unknown symbol"),
+                    _ => {
+                        let token = &ln[k..k + c.len_utf8()];
+                        return Err(ParseError::at(s, token, "'>', 'v' or '.'"));
+                    }
                 };
 
                 x += 1;
@@ -35,12 +41,12 @@ impl State {
             y += 1;
         }
 
-        State {
+        Ok(State {
             board_width: x,
             board_height: y,
             east_herd,
             south_herd,
-        }
+        })
     }
 
     fn next(&self) -> State {
@@ -86,39 +92,50 @@ impl State {
                 }
             }
 
-            println!("");
+            println!();
         }
     }
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day25.txt").expect("Could not read file");
-    let mut state: State = State::from(input_str);
-    let mut count: u32 = 1;
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+    type Input = State;
+
+    fn parse(input: &str) -> Result<State, ParseError> {
+        State::from(input)
+    }
+
+    fn part1(state: &State) -> Answer {
+        let mut state: State = state.clone();
+        let mut count: u32 = 1;
 
-    loop {
-        let next_state = state.next();
+        loop {
+            let next_state = state.next();
 
-        if state == next_state {
-            break;
-        } else {
-            count += 1;
-            state = next_state;
+            if state == next_state {
+                break;
+            } else {
+                count += 1;
+                state = next_state;
+            }
         }
-    }
 
-    count.to_string()
-}
+        Ok(count.to_string())
+    }
 
-pub fn ex2() -> String {
-    String::from("N/A")
+    fn part2(_state: &State) -> Answer {
+        Ok(String::from("N/A"))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::ex1;
+    use super::Day25;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "568");
+        let input = Day25::parse(&fs::read_to_string("inputs/day25.txt").unwrap()).unwrap();
+        assert_eq!(Day25::part1(&input).unwrap(), "568");
     }
 }
@@ -1,103 +1,185 @@
-use std::fs;
-
-fn find_value<'a>(mut selection: Vec<&'a str>, negate: bool) -> &'a str {
-    // preprocessing
-    selection.sort();
-    selection.dedup();
-
-    let vec_len = selection[0].len();
-
-    for k in 0..vec_len {
-        // compute most frequent values
-        let mut size_counter: u16 = 0;
-        let mut v: u16 = 0;
-        for line in selection.iter() {
-            size_counter += 1;
-            if line.as_bytes()[k] == '1' as u8 {
-                v += 1;
+use crate::error::{parse_lines, ParseError};
+use crate::solution::{Answer, Solution};
+use num_bigint::BigUint;
+
+pub struct Day3;
+
+/// Maximum number of binary digits of the diagnostic report.
+pub const MAX_WIDTH: usize = u128::BITS as usize;
+
+/// Numbers of the diagnostic report, all of them have `width` binary digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub width: usize,
+    /// The numbers in ascending order.
+    pub numbers: Vec<u128>,
+}
+
+/// Counts the ones in each column of the numbers. The counters are bit-sliced: bit `k` of
+/// `planes[j]` is bit `j` of the counter of column `k`, so adding a number increments the
+/// counters of all of the columns at once with a ripple carry through the planes.
+pub fn column_counts(numbers: &[u128], width: usize) -> Vec<usize> {
+    let mut planes: Vec<u128> = Vec::new();
+
+    for n in numbers {
+        let mut carry = *n;
+        for plane in planes.iter_mut() {
+            if carry == 0 {
+                break;
             }
+            (*plane, carry) = (*plane ^ carry, *plane & carry);
         }
+        if carry != 0 {
+            planes.push(carry);
+        }
+    }
 
-        let (x, y) = if 2 * v >= size_counter {
-            ('1' as u8, '0' as u8)
-        } else {
-            ('0' as u8, '1' as u8)
-        };
+    (0..width)
+        .map(|k| {
+            planes
+                .iter()
+                .enumerate()
+                .map(|(j, plane)| ((plane >> k & 1) as usize) << j)
+                .sum()
+        })
+        .collect()
+}
+
+/// Repeatedly keeps the numbers with the most common value of the next bit, or the least
+/// common one if `least_common` is set, until a single number is left. As the numbers are
+/// sorted and all of the kept ones share the preceding bits, they are always a contiguous
+/// range split by the next bit. A group that is empty is never kept.
+fn find_value(report: &Report, least_common: bool) -> u128 {
+    let mut selection = &report.numbers[..];
 
+    for k in (0..report.width).rev() {
         if selection.len() == 1 {
             break;
-        } else {
-            selection = selection
-                .iter()
-                .filter(|v| v.as_bytes()[k] == (if negate { x } else { y }))
-                .map(|x| *x)
-                .collect();
         }
+
+        let split = selection.partition_point(|n| n >> k & 1 == 0);
+        let (zeros, ones) = selection.split_at(split);
+        let keep_ones = (ones.len() >= zeros.len()) != least_common;
+
+        selection = if (keep_ones && !ones.is_empty()) || zeros.is_empty() {
+            ones
+        } else {
+            zeros
+        };
     }
 
-    assert!(selection.len() == 1);
+    // the remaining numbers are all equal
     selection[0]
 }
 
-pub fn ex1() -> String {
-    // load lines
-    let content_str = fs::read_to_string("inputs/day3.txt").expect("Could not read file");
+/// Product of two binary numbers, which may not fit into `u128`.
+fn product(a: u128, b: u128) -> String {
+    (BigUint::from(a) * BigUint::from(b)).to_string()
+}
 
-    // allocate vector
-    let vec_len = content_str.lines().next().expect("Empty file").len();
-    let mut vec: Vec<u16> = Vec::with_capacity(vec_len);
-    for _ in 0..vec_len {
-        vec.push(0);
-    }
+fn power_consumption(report: &Report) -> String {
+    let size = report.numbers.len();
+    let gamma = column_counts(&report.numbers, report.width)
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| 2 * *count >= size)
+        .fold(0u128, |gamma, (k, _)| gamma | 1 << k);
+    let epsilon = !gamma & (u128::MAX >> (MAX_WIDTH - report.width));
 
-    // compute most frequent values
-    let mut size_counter: u16 = 0;
-    for line in content_str.lines() {
-        size_counter += 1;
-        for (k, c) in line.chars().enumerate() {
-            if c == '1' {
-                vec[k] += 1;
-            }
-        }
-    }
+    product(gamma, epsilon)
+}
 
-    // compute result
-    let mut gamma: u16 = 0;
-    let mut epsilon: u16 = 0;
-    for v in vec.iter() {
-        gamma += 1;
-        if 2 * v >= size_counter {
-        } else {
-            epsilon += 1;
+fn life_support_rating(report: &Report) -> String {
+    product(find_value(report, false), find_value(report, true))
+}
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+    type Input = Report;
+
+    fn parse(input: &str) -> Result<Report, ParseError> {
+        // all of the numbers have the width of the first one
+        let width = input.lines().next().map_or(0, str::len).min(MAX_WIDTH);
+        if width == 0 {
+            return Err(ParseError::at(input, &input[..0], "binary number"));
         }
-        gamma *= 2;
-        epsilon *= 2;
-    }
-    gamma /= 2;
-    epsilon /= 2;
+        let expected_width = format!("{} binary digits", width);
 
-    // print result
-    (gamma as u32 * epsilon as u32).to_string()
-}
+        let mut numbers = parse_lines(input, |ln| {
+            if let Some((k, c)) = ln.char_indices().find(|&(_, c)| c != '0' && c != '1') {
+                Err(ParseError::at(ln, &ln[k..k + c.len_utf8()], "binary digit"))
+            } else if ln.len() < width {
+                Err(ParseError::at_end(ln, &expected_width))
+            } else if ln.len() > width {
+                Err(ParseError::at(
+                    ln,
+                    &ln[width..],
+                    &format!("end of line after {}", expected_width),
+                ))
+            } else {
+                Ok(u128::from_str_radix(ln, 2).unwrap())
+            }
+        })?;
 
-pub fn ex2() -> String {
-    // load lines
-    let content_str = fs::read_to_string("inputs/day3.txt").expect("Could not read file");
+        if numbers.is_empty() {
+            return Err(ParseError::at_end(input, "binary number"));
+        }
+        numbers.sort_unstable();
+        Ok(Report { width, numbers })
+    }
 
-    let oxygen: u16 =
-        u16::from_str_radix(find_value(content_str.lines().collect(), false), 2).expect("");
-    let co2: u16 =
-        u16::from_str_radix(find_value(content_str.lines().collect(), true), 2).expect("");
+    fn part1(report: &Report) -> Answer {
+        Ok(power_consumption(report))
+    }
 
-    (oxygen as u32 * co2 as u32).to_string()
+    fn part2(report: &Report) -> Answer {
+        Ok(life_support_rating(report))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::{column_counts, Day3};
+    use crate::solution::Solution;
+    use num_bigint::BigUint;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "2694510");
-        assert_eq!(ex2(), "6775520");
+        let input = Day3::parse(&fs::read_to_string("inputs/day3.txt").unwrap()).unwrap();
+        assert_eq!(Day3::part1(&input).unwrap(), "2261546");
+        assert_eq!(Day3::part2(&input).unwrap(), "6775520");
+    }
+
+    #[test]
+    fn test_wide_numbers() {
+        let ones = "1".repeat(100);
+        let zeros = "0".repeat(100);
+        let input = Day3::parse(&format!("{}\n{}\n{}\n", ones, ones, zeros)).unwrap();
+        // gamma and the oxygen rating are 2^100 - 1, while epsilon and the CO2 rating are 0
+        assert_eq!(Day3::part1(&input).unwrap(), "0");
+        assert_eq!(Day3::part2(&input).unwrap(), "0");
+
+        let input = Day3::parse(&format!("1{}\n0{}\n1{}\n", zeros, ones, zeros)).unwrap();
+        assert_eq!(
+            Day3::part1(&input).unwrap(),
+            (BigUint::from(1u128 << 100) * ((1u128 << 100) - 1)).to_string()
+        );
+
+        let err = Day3::parse("10110\n1011\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 5));
+        assert_eq!(err.expected, "5 binary digits");
+        let err = Day3::parse("10110\n101101\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 6, "1"));
+        assert!(Day3::parse(&"1".repeat(129)).is_err());
+        assert!(Day3::parse("1é01\n").is_err());
+        assert!(Day3::parse("\n").is_err());
+        assert!(Day3::parse("\n1\n").is_err());
+        assert!(Day3::parse("").is_err());
+
+        let numbers: Vec<u128> = (0..1000).collect();
+        let counts = column_counts(&numbers, 10);
+        for (k, count) in counts.iter().enumerate() {
+            assert_eq!(*count, numbers.iter().filter(|n| *n >> k & 1 == 1).count());
+        }
     }
 }
@@ -1,136 +1,383 @@
-use std::fs;
+use crate::error::{parse_token, ParseError};
+use crate::solution::{Answer, Solution};
+use std::collections::HashMap;
 use std::iter::Iterator;
 use std::str::Lines;
 
-#[derive(Debug)]
-struct Board {
-    pub numbers: [[u8; 5]; 5],
-    pub hits: [[bool; 5]; 5],
+pub struct Day4;
+
+pub type Number = u32;
+
+/// Position of a number on one of the boards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub board: usize,
+    pub row: usize,
+    pub col: usize,
+}
+
+/// Drawn numbers together with all the boards in play.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub numbers: Vec<Number>,
+    pub boards: Vec<Board>,
+    /// All of the cells with the given number, ordered by the board.
+    pub index: HashMap<Number, Vec<Cell>>,
+}
+
+impl Game {
+    pub fn new(numbers: Vec<Number>, boards: Vec<Board>) -> Game {
+        let mut index: HashMap<Number, Vec<Cell>> = HashMap::new();
+
+        for (k, board) in boards.iter().enumerate() {
+            for (i, n) in board.numbers.iter().enumerate() {
+                index.entry(*n).or_default().push(Cell {
+                    board: k,
+                    row: i / board.cols,
+                    col: i % board.cols,
+                });
+            }
+        }
+
+        Game {
+            numbers,
+            boards,
+            index,
+        }
+    }
+}
+
+/// Board of `rows` × `cols` numbers stored row by row, together with the number of hits
+/// in each of its rows and columns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Board {
+    pub rows: usize,
+    pub cols: usize,
+    pub numbers: Vec<Number>,
+    pub hits: Vec<bool>,
+    pub row_hits: Vec<usize>,
+    pub col_hits: Vec<usize>,
+    pub total_hits: usize,
 }
 
 impl Board {
-    pub fn new() -> Board {
+    pub fn new(rows: usize, cols: usize) -> Board {
         Board {
-            numbers: [[0u8; 5]; 5],
-            hits: [[false; 5]; 5],
+            rows,
+            cols,
+            numbers: vec![0; rows * cols],
+            hits: vec![false; rows * cols],
+            row_hits: vec![0; rows],
+            col_hits: vec![0; cols],
+            total_hits: 0,
         }
     }
 
-    pub fn winning(&self) -> bool {
-        let mut result = false;
+    pub fn is_hit(&self, row: usize, col: usize) -> bool {
+        self.hits[row * self.cols + col]
+    }
 
-        for i in 0..5 {
-            result |= self.hits[i][0]
-                & self.hits[i][1]
-                & self.hits[i][2]
-                & self.hits[i][3]
-                & self.hits[i][4];
+    /// Marks the cell, returns whether it was not marked before.
+    pub fn mark(&mut self, row: usize, col: usize) -> bool {
+        let hit = &mut self.hits[row * self.cols + col];
+        if *hit {
+            return false;
         }
 
-        for i in 0..5 {
-            result |= self.hits[0][i]
-                & self.hits[1][i]
-                & self.hits[2][i]
-                & self.hits[3][i]
-                & self.hits[4][i];
-        }
+        *hit = true;
+        self.row_hits[row] += 1;
+        self.col_hits[col] += 1;
+        self.total_hits += 1;
+        true
+    }
 
-        result
+    /// Sum of the numbers that are not marked.
+    pub fn unmarked_sum(&self) -> u64 {
+        self.numbers
+            .iter()
+            .zip(&self.hits)
+            .filter(|(_, hit)| !**hit)
+            .map(|(n, _)| *n as u64)
+            .sum()
     }
 }
 
-fn load_numbers(line: Option<&str>) -> Vec<u8> {
-    line.expect("")
-        .split(",")
-        .map(|x| x.parse::<u8>().unwrap())
-        .collect()
+/// Pattern of marked numbers that wins the board.
+pub trait WinCondition {
+    fn is_won(&self, board: &Board) -> bool;
+
+    /// Whether the board is won after marking the given cell, when it was not won before.
+    /// Only the patterns containing the cell need to be checked.
+    fn is_won_with(&self, board: &Board, _row: usize, _col: usize) -> bool {
+        self.is_won(board)
+    }
 }
 
-fn load_boards(mut lines: Lines) -> Vec<Board> {
-    let mut boards = Vec::new();
+/// Any completely marked row.
+pub struct Rows;
 
-    while let Some(_) = lines.next() {
-        let mut board = Board::new();
+impl WinCondition for Rows {
+    fn is_won(&self, board: &Board) -> bool {
+        board.row_hits.contains(&board.cols)
+    }
 
-        for i in 0..5 {
-            board.numbers[i] = lines
-                .next()
-                .unwrap()
-                .split_ascii_whitespace()
-                .map(|s| s.parse::<u8>().unwrap())
-                .collect::<Vec<u8>>()
-                .try_into()
-                .unwrap();
-        }
+    fn is_won_with(&self, board: &Board, row: usize, _col: usize) -> bool {
+        board.row_hits[row] == board.cols
+    }
+}
 
-        boards.push(board);
+/// Any completely marked column.
+pub struct Columns;
+
+impl WinCondition for Columns {
+    fn is_won(&self, board: &Board) -> bool {
+        board.col_hits.contains(&board.rows)
+    }
+
+    fn is_won_with(&self, board: &Board, _row: usize, col: usize) -> bool {
+        board.col_hits[col] == board.rows
     }
+}
+
+/// Either of the two diagonals completely marked, only square boards have them.
+pub struct Diagonals;
 
-    boards
+impl WinCondition for Diagonals {
+    fn is_won(&self, board: &Board) -> bool {
+        let n = board.rows;
+
+        board.rows == board.cols
+            && ((0..n).all(|i| board.is_hit(i, i)) || (0..n).all(|i| board.is_hit(i, n - 1 - i)))
+    }
+
+    fn is_won_with(&self, board: &Board, row: usize, col: usize) -> bool {
+        let n = board.rows;
+
+        board.rows == board.cols
+            && ((row == col && (0..n).all(|i| board.is_hit(i, i)))
+                || (row + col == n - 1 && (0..n).all(|i| board.is_hit(i, n - 1 - i))))
+    }
+}
+
+/// All of the numbers of the board marked.
+pub struct FullCard;
+
+impl WinCondition for FullCard {
+    fn is_won(&self, board: &Board) -> bool {
+        board.total_hits == board.numbers.len()
+    }
 }
 
-fn score(board: &Board, number: u8) -> u32 {
-    let mut sum: u32 = 0;
+/// All of the four corners of the board marked.
+pub struct FourCorners;
 
-    for i in 0..5 {
-        for j in 0..5 {
-            if !board.hits[i][j] {
-                sum += board.numbers[i][j] as u32;
+impl WinCondition for FourCorners {
+    fn is_won(&self, board: &Board) -> bool {
+        let (last_row, last_col) = (board.rows - 1, board.cols - 1);
+
+        board.is_hit(0, 0)
+            && board.is_hit(0, last_col)
+            && board.is_hit(last_row, 0)
+            && board.is_hit(last_row, last_col)
+    }
+}
+
+/// Win conditions of the puzzle, a completely marked row or column.
+pub const STANDARD_RULES: [&dyn WinCondition; 2] = [&Rows, &Columns];
+
+/// Board that won the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Win {
+    /// Index of the board in the game.
+    pub board: usize,
+    /// Index of the drawn number that made the board win.
+    pub draw: usize,
+    pub score: u64,
+}
+
+fn load_numbers(input: &str, line: Option<&str>) -> Result<Vec<Number>, ParseError> {
+    line.ok_or_else(|| ParseError::at_end(input, "drawn numbers"))?
+        .split(',')
+        .map(|x| parse_token(input, x, "drawn number"))
+        .collect()
+}
+
+/// Loads the boards separated by empty lines. All of them have the size of the first one.
+fn load_boards(input: &str, lines: Lines) -> Result<Vec<Board>, ParseError> {
+    let mut boards: Vec<Board> = Vec::new();
+    let mut lines = lines.peekable();
+
+    while let Some(separator) = lines.next() {
+        if !separator.is_empty() {
+            return Err(ParseError::at(input, separator, "empty line"));
+        }
+
+        let mut board = Board::default();
+
+        // the rows continue up to the next empty line
+        while let Some(line) = lines.next_if(|ln| !ln.is_empty()) {
+            let row = line
+                .split_ascii_whitespace()
+                .map(|s| parse_token(input, s, "board number"))
+                .collect::<Result<Vec<Number>, ParseError>>()?;
+
+            let cols = match boards.first() {
+                Some(first) => first.cols,
+                None if board.rows == 0 => row.len(),
+                None => board.cols,
+            };
+            if row.is_empty() || row.len() != cols {
+                let expected = format!("{} board numbers", cols.max(1));
+                return Err(ParseError::at(input, line, &expected));
             }
+
+            board.cols = cols;
+            board.rows += 1;
+            board.numbers.extend(row);
+        }
+
+        if board.rows == 0 || boards.first().is_some_and(|b| b.rows != board.rows) {
+            return Err(match lines.peek() {
+                Some(line) => ParseError::at(input, line, "board row"),
+                None => ParseError::at_end(input, "board row"),
+            });
         }
+        boards.push(Board {
+            numbers: board.numbers,
+            ..Board::new(board.rows, board.cols)
+        });
     }
-    sum * (number as u32)
+
+    Ok(boards)
 }
 
-fn general_solution(last_board: bool) -> String {
-    let input_str = fs::read_to_string("inputs/day4.txt").expect("Could not read file");
-    let mut lines = input_str.lines();
-    let numbers = load_numbers(lines.next());
-    let mut boards: Vec<Board> = load_boards(lines);
+/// Plays the whole game, returns the boards in the order in which they won, boards winning
+/// with the same number are ordered by their index. A board wins once any of the conditions
+/// holds and it is not played any further. Only the cells with the drawn number are visited.
+pub fn replay(game: &Game, conditions: &[&dyn WinCondition]) -> Vec<Win> {
+    let mut boards: Vec<Board> = game.boards.clone();
+    let mut won = vec![false; boards.len()];
+    let mut wins = Vec::new();
 
-    for number in numbers {
-        for idx in 0..boards.len() {
-            for i in 0..5 {
-                for j in 0..5 {
-                    if boards[idx].numbers[i][j] == number {
-                        boards[idx].hits[i][j] = true;
-                    }
-                }
+    for (draw, number) in game.numbers.iter().copied().enumerate() {
+        let Some(cells) = game.index.get(&number) else {
+            continue;
+        };
+
+        // mark all of the occurrences on a board before checking it, so that the score is right
+        for board_cells in cells.chunk_by(|a, b| a.board == b.board) {
+            let idx = board_cells[0].board;
+            if won[idx] {
+                continue;
             }
-        }
 
-        for idx in (0..boards.len()).rev() {
-            if boards[idx].winning() {
-                if !last_board {
-                    return score(&boards[idx], number).to_string();
-                } else {
-                    if boards.len() == 1 {
-                        return score(&boards[0], number).to_string();
-                    } else {
-                        boards.remove(idx);
-                    }
-                }
+            let board = &mut boards[idx];
+            let marked: Vec<&Cell> = board_cells
+                .iter()
+                .filter(|cell| board.mark(cell.row, cell.col))
+                .collect();
+            let is_won = marked.iter().any(|cell| {
+                conditions
+                    .iter()
+                    .any(|c| c.is_won_with(board, cell.row, cell.col))
+            });
+
+            if is_won {
+                won[idx] = true;
+                wins.push(Win {
+                    board: idx,
+                    draw,
+                    score: board.unmarked_sum() * number as u64,
+                });
             }
         }
     }
 
-    panic!("No winners, undefined score")
+    wins
 }
 
-pub fn ex1() -> String {
-    general_solution(false)
+fn general_solution(game: &Game, last_board: bool) -> String {
+    let wins = replay(game, &STANDARD_RULES);
+    let win = if last_board {
+        wins.last()
+    } else {
+        wins.first()
+    };
+
+    win.expect("No winners, undefined score").score.to_string()
 }
 
-pub fn ex2() -> String {
-    general_solution(true)
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+    type Input = Game;
+
+    fn parse(input: &str) -> Result<Game, ParseError> {
+        let mut lines = input.lines();
+        let numbers = load_numbers(input, lines.next())?;
+        Ok(Game::new(numbers, load_boards(input, lines)?))
+    }
+
+    fn part1(game: &Game) -> Answer {
+        Ok(general_solution(game, false))
+    }
+
+    fn part2(game: &Game) -> Answer {
+        Ok(general_solution(game, true))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::{replay, Cell, Columns, Day4, Diagonals, FourCorners, FullCard, Rows, Win};
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "50008");
-        assert_eq!(ex2(), "17408");
+        let input = Day4::parse(&fs::read_to_string("inputs/day4.txt").unwrap()).unwrap();
+        assert_eq!(Day4::part1(&input).unwrap(), "50008");
+        assert_eq!(Day4::part2(&input).unwrap(), "17408");
+    }
+
+    #[test]
+    fn test_configurable_game() {
+        let game =
+            Day4::parse("5,1,9,7,4,6,1000000\n\n1 2 3\n4 5 6\n\n9 8 7\n6 5 1000000\n").unwrap();
+        assert_eq!((game.boards[0].rows, game.boards[0].cols), (2, 3));
+        assert_eq!(
+            game.index[&5],
+            vec![
+                Cell {
+                    board: 0,
+                    row: 1,
+                    col: 1
+                },
+                Cell {
+                    board: 1,
+                    row: 1,
+                    col: 1
+                }
+            ]
+        );
+        assert!(Day4::parse("1\n\n1 2 3\n4 5\n").is_err());
+        assert!(Day4::parse("1\n\n1 2\n3 4\n\n1 2\n").is_err());
+
+        assert_eq!(
+            replay(&game, &[&FourCorners]),
+            vec![Win {
+                board: 1,
+                draw: 6,
+                score: 8 * 1000000
+            }]
+        );
+        assert_eq!(replay(&game, &[&Diagonals]), vec![]);
+        assert_eq!(replay(&game, &[&FullCard]), vec![]);
+
+        let wins = replay(&game, &[&Rows, &Columns]);
+        assert_eq!(
+            wins.iter().map(|w| (w.board, w.draw)).collect::<Vec<_>>(),
+            vec![(0, 4), (1, 5)]
+        );
+        assert_eq!(wins[0].score, (2 + 3 + 6) * 4);
+        assert_eq!(wins[1].score, (8 + 1000000) * 6);
     }
 }
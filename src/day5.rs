@@ -1,8 +1,22 @@
+use crate::error::{parse_lines, parse_token, ParseError};
+use crate::solution::{Answer, Solution};
 use std::collections::HashMap;
-use std::fs;
 
+pub struct Day5;
+
+pub type Coord = i64;
+
+/// Line segment from `(x1, y1)` to `(x2, y2)`.
 #[derive(Debug)]
-struct Segment(u16, u16, u16, u16);
+pub struct Segment(pub Coord, pub Coord, pub Coord, pub Coord);
+
+fn gcd(a: Coord, b: Coord) -> Coord {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
 
 impl Segment {
     fn is_vertical(&self) -> bool {
@@ -13,89 +27,363 @@ impl Segment {
         self.1 == self.3
     }
 
-    fn is_diagonal(&self) -> bool {
-        let deg_45 = (self.0 as i16 - self.2 as i16).abs() == (self.1 as i16 - self.3 as i16).abs();
-        !self.is_horizontal() & !self.is_vertical() & deg_45
+    /// Direction from the start of the segment to its end, or `None` if it does not fit in
+    /// the coordinates.
+    fn direction(&self) -> Option<(Coord, Coord)> {
+        let dx = self.2.checked_sub(self.0)?;
+        let dy = self.3.checked_sub(self.1)?;
+        // the gcd needs the absolute values as well
+        dx.checked_abs()?;
+        dy.checked_abs()?;
+
+        Some((dx, dy))
     }
-}
 
-fn get_segment(line: &str) -> Segment {
-    const ERR_MSG: &str = "Incorrect line format";
-    if let [x1, y1, _arrow, x2, y2] =
-        line.split(&[',', ' '][..]).take(5).collect::<Vec<&str>>()[..5]
-    {
-        let s = Segment(
-            x1.parse::<u16>().expect(ERR_MSG),
-            y1.parse::<u16>().expect(ERR_MSG),
-            x2.parse::<u16>().expect(ERR_MSG),
-            y2.parse::<u16>().expect(ERR_MSG),
-        );
-        return s;
+    /// All of the lattice points of the segment, from its start to its end. Consecutive
+    /// points differ by the direction of the segment divided by the gcd of its coordinates,
+    /// so the points are exact for segments at any angle.
+    ///
+    /// Panics if the direction of the segment overflows the coordinates, which the parser
+    /// rejects.
+    pub fn points(&self) -> impl Iterator<Item = (Coord, Coord)> {
+        let (dx, dy) = self
+            .direction()
+            .expect("direction of the segment overflows the coordinates");
+        let steps = gcd(dx.abs(), dy.abs());
+        let (step_x, step_y) = if steps == 0 {
+            (0, 0)
+        } else {
+            (dx / steps, dy / steps)
+        };
+        let (x, y) = (self.0, self.1);
+
+        (0..=steps).map(move |i| (x + i * step_x, y + i * step_y))
     }
-    panic!("{}", ERR_MSG);
 }
 
-fn general_solution(count_diagonals: bool) -> String {
-    let mut map: HashMap<(u16, u16), u16> = HashMap::new();
+fn get_point(line: &str, point: &str) -> Result<(Coord, Coord), ParseError> {
+    let (x, y) = point
+        .split_once(',')
+        .ok_or_else(|| ParseError::at(line, point, "point in the form 'x,y'"))?;
+    Ok((
+        parse_token(line, x, "coordinate")?,
+        parse_token(line, y, "coordinate")?,
+    ))
+}
 
-    let input_str = fs::read_to_string("inputs/day5.txt").expect("Could not read file");
-    let segments = input_str.lines().map(|ln| get_segment(ln));
+fn get_segment(line: &str) -> Result<Segment, ParseError> {
+    let (start, end) = line
+        .split_once(" -> ")
+        .ok_or_else(|| ParseError::at(line, line, "segment in the form 'x1,y1 -> x2,y2'"))?;
+    let (x1, y1) = get_point(line, start)?;
+    let (x2, y2) = get_point(line, end)?;
+    let segment = Segment(x1, y1, x2, y2);
+    if segment.direction().is_none() {
+        return Err(ParseError::at(
+            line,
+            line,
+            "segment whose extent fits in a coordinate",
+        ));
+    }
 
-    for segment in segments {
-        if segment.is_vertical() {
-            let (x1, y1, _, y2) = if segment.1 < segment.3 {
-                (segment.0, segment.1, segment.2, segment.3)
-            } else {
-                (segment.2, segment.3, segment.0, segment.1)
-            };
+    Ok(segment)
+}
 
-            for i in y1..(y2 + 1) {
-                let key = (x1, i);
-                *map.entry(key).or_insert(0) += 1;
+/// Number of segments covering each of the points, segments that are neither horizontal nor
+/// vertical are only included with `count_diagonals`.
+pub fn coverage(segments: &[Segment], count_diagonals: bool) -> HashMap<(Coord, Coord), usize> {
+    let mut map: HashMap<(Coord, Coord), usize> = HashMap::new();
+
+    for segment in segments {
+        if segment.is_vertical() || segment.is_horizontal() || count_diagonals {
+            for point in segment.points() {
+                *map.entry(point).or_insert(0) += 1;
             }
-        } else if segment.is_horizontal() {
-            let (x1, y1, x2, _) = if segment.0 < segment.2 {
-                (segment.0, segment.1, segment.2, segment.3)
-            } else {
-                (segment.2, segment.3, segment.0, segment.1)
-            };
+        }
+    }
+
+    map
+}
 
-            for i in x1..(x2 + 1) {
-                let key = (i, y1);
-                *map.entry(key).or_insert(0) += 1;
+/// Largest side of the heatmaps written by the `day5_heatmap` example.
+pub const HEATMAP_SIDE: usize = 2048;
+
+/// Largest brightness of a plain PGM image.
+const PGM_MAXVAL: usize = 65535;
+
+/// Heatmap of the coverage as a plain PGM image spanning the covered points, the brightness
+/// of a pixel is the number of segments covering the point, up to the PGM limit of 65535.
+///
+/// Maps wider or taller than `max_side` points are downsampled by the same factor in both
+/// directions, each pixel showing the most covered point of its block, so the image has at
+/// most `max_side` pixels on a side. An empty map is a single black pixel.
+pub fn heatmap_pgm(map: &HashMap<(Coord, Coord), usize>, max_side: usize) -> String {
+    let xs = map.keys().map(|(x, _)| *x);
+    let ys = map.keys().map(|(_, y)| *y);
+    let (min_x, max_x) = (xs.clone().min().unwrap_or(0), xs.max().unwrap_or(0));
+    let (min_y, max_y) = (ys.clone().min().unwrap_or(0), ys.max().unwrap_or(0));
+    // spans of up to 2^64 points do not fit in the coordinates
+    let (span_x, span_y) = (
+        max_x as i128 - min_x as i128 + 1,
+        max_y as i128 - min_y as i128 + 1,
+    );
+    let max_side = max_side.max(1) as i128;
+    let scale = ((span_x.max(span_y) + max_side - 1) / max_side).max(1);
+    let (width, height) = (
+        ((span_x + scale - 1) / scale) as usize,
+        ((span_y + scale - 1) / scale) as usize,
+    );
+
+    let mut pixels = vec![0; width * height];
+    for (&(x, y), &covered) in map {
+        let column = ((x as i128 - min_x as i128) / scale) as usize;
+        let row = ((y as i128 - min_y as i128) / scale) as usize;
+        let pixel = &mut pixels[row * width + column];
+        *pixel = covered.min(PGM_MAXVAL).max(*pixel);
+    }
+
+    let maxval = pixels.iter().max().unwrap_or(&0).max(&1);
+    let mut pgm = format!("P2\n{} {}\n{}\n", width, height, maxval);
+    for row in pixels.chunks(width) {
+        let row: Vec<String> = row.iter().map(usize::to_string).collect();
+        pgm += &row.join(" ");
+        pgm.push('\n');
+    }
+
+    pgm
+}
+
+/// Lattice line given by its primitive direction `(sx, sy)`, normalized to point right
+/// or up, and by `sx * y - sy * x`, which is the same for all of its points.
+type LineKey = (i128, i128, i128);
+
+/// Segments lying on a single line, as intervals of `sx * x + sy * y` of their points.
+/// Consecutive lattice points of the line differ by `sx² + sy²` in this parameter.
+#[derive(Default)]
+struct Line {
+    spacing: i128,
+    starts: Vec<i128>,
+    ends: Vec<i128>,
+}
+
+impl Line {
+    /// Number of the segments covering the point with the parameter `t`.
+    fn coverage(&self, t: i128) -> usize {
+        self.starts.partition_point(|s| *s <= t) - self.ends.partition_point(|e| *e < t)
+    }
+
+    /// Number of the lattice points covered by at least `k` of the segments.
+    fn count_covered(&self, k: usize) -> i128 {
+        // the coverage changes at the start of a segment and right after its end
+        let mut events: Vec<(i128, isize)> = self.starts.iter().map(|s| (*s, 1)).collect();
+        events.extend(self.ends.iter().map(|e| (e + self.spacing, -1)));
+        events.sort_unstable();
+
+        let mut count = 0;
+        let mut covering: isize = 0;
+        for (event, next) in events.iter().zip(events.iter().skip(1)) {
+            covering += event.1;
+            if covering >= k as isize {
+                count += (next.0 - event.0) / self.spacing;
             }
-        } else if segment.is_diagonal() & count_diagonals {
-            let (x1, y1, x2, y2) = if segment.0 < segment.2 {
-                (segment.0, segment.1, segment.2, segment.3)
-            } else {
-                (segment.2, segment.3, segment.0, segment.1)
-            };
+        }
+
+        count
+    }
+}
+
+/// Line of the segment and the interval of its parameter, see `LineKey` and `Line`.
+/// A segment with a single point is treated as horizontal.
+fn line_interval(segment: &Segment) -> (LineKey, i128, i128) {
+    let (x1, y1, x2, y2) = (
+        segment.0 as i128,
+        segment.1 as i128,
+        segment.2 as i128,
+        segment.3 as i128,
+    );
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let steps = gcd(dx.abs() as Coord, dy.abs() as Coord) as i128;
+    let (sx, sy) = match steps {
+        0 => (1, 0),
+        _ if dx < 0 || (dx == 0 && dy < 0) => (-dx / steps, -dy / steps),
+        _ => (dx / steps, dy / steps),
+    };
+    let (t1, t2) = (sx * x1 + sy * y1, sx * x2 + sy * y2);
 
-            let sgn: i16 = if y1 < y2 { 1 } else { -1 };
-            for i in 0..(x2 - x1 + 1) {
-                let key = (x1 + i, (y1 as i16 + sgn * i as i16) as u16);
-                *map.entry(key).or_insert(0) += 1;
+    ((sx, sy, sx * y1 - sy * x1), t1.min(t2), t1.max(t2))
+}
+
+/// Lattice point where two non-parallel lines cross, if there is one.
+fn crossing((ax, ay, a): LineKey, (bx, by, b): LineKey) -> Option<(i128, i128)> {
+    let det = ax * by - ay * bx;
+    let (x, y) = (a * bx - ax * b, by * a - ay * b);
+
+    (det != 0 && x % det == 0 && y % det == 0).then(|| (x / det, y / det))
+}
+
+/// Counts the lattice points covered by at least `k` segments without enumerating them.
+/// The segments are grouped by their line and the points covered on each line are counted
+/// by a sweep over the ends of its segments. Only the points where segments of different
+/// lines cross are enumerated, as their coverage is the sum of the coverage on each line.
+pub fn sweep_count(segments: &[Segment], count_diagonals: bool, k: usize) -> usize {
+    assert!(k > 0, "points have to be covered at least once");
+
+    let mut lines: HashMap<LineKey, Line> = HashMap::new();
+    let mut intervals = Vec::new();
+    for segment in segments {
+        if !(segment.is_vertical() || segment.is_horizontal() || count_diagonals) {
+            continue;
+        }
+
+        let (key, t1, t2) = line_interval(segment);
+        let line = lines.entry(key).or_default();
+        line.spacing = key.0 * key.0 + key.1 * key.1;
+        line.starts.push(t1);
+        line.ends.push(t2);
+        intervals.push((key, t1, t2));
+    }
+    for line in lines.values_mut() {
+        line.starts.sort_unstable();
+        line.ends.sort_unstable();
+    }
+
+    // lines going through each of the crossings of segments
+    let mut crossings: HashMap<(i128, i128), Vec<LineKey>> = HashMap::new();
+    for (i, (a, a1, a2)) in intervals.iter().enumerate() {
+        for (b, b1, b2) in intervals[i + 1..].iter() {
+            let Some((x, y)) = crossing(*a, *b) else {
+                continue;
+            };
+            let (ta, tb) = (a.0 * x + a.1 * y, b.0 * x + b.1 * y);
+            if (*a1..=*a2).contains(&ta) && (*b1..=*b2).contains(&tb) {
+                let through = crossings.entry((x, y)).or_default();
+                for key in [a, b] {
+                    if !through.contains(key) {
+                        through.push(*key);
+                    }
+                }
             }
         }
     }
 
-    map.iter().filter(|(_, v)| **v >= 2).count().to_string()
+    let mut count: i128 = lines.values().map(|line| line.count_covered(k)).sum();
+    for ((x, y), through) in crossings {
+        let per_line: Vec<usize> = through
+            .iter()
+            .map(|key| lines[key].coverage(key.0 * x + key.1 * y))
+            .collect();
+
+        // replace the counts of the point on the separate lines by its total coverage
+        count -= per_line.iter().filter(|c| **c >= k).count() as i128;
+        count += (per_line.iter().sum::<usize>() >= k) as i128;
+    }
+
+    count as usize
 }
 
-pub fn ex1() -> String {
-    general_solution(false)
+/// Counts the points covered by at least two segments.
+pub fn general_solution(segments: &[Segment], count_diagonals: bool) -> String {
+    let map = coverage(segments, count_diagonals);
+    map.values().filter(|v| **v >= 2).count().to_string()
 }
 
-pub fn ex2() -> String {
-    general_solution(true)
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+    type Input = Vec<Segment>;
+
+    fn parse(input: &str) -> Result<Vec<Segment>, ParseError> {
+        parse_lines(input, get_segment)
+    }
+
+    fn part1(segments: &Vec<Segment>) -> Answer {
+        Ok(general_solution(segments, false))
+    }
+
+    fn part2(segments: &Vec<Segment>) -> Answer {
+        Ok(general_solution(segments, true))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::{coverage, heatmap_pgm, sweep_count, Day5, Segment, HEATMAP_SIDE};
+    use crate::solution::Solution;
+    use std::collections::HashMap;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "5690");
-        assert_eq!(ex2(), "17741");
+        let input = Day5::parse(&fs::read_to_string("inputs/day5.txt").unwrap()).unwrap();
+        assert_eq!(Day5::part1(&input).unwrap(), "5690");
+        assert_eq!(Day5::part2(&input).unwrap(), "17741");
+    }
+
+    #[test]
+    fn test_any_angle() {
+        let points: Vec<(i64, i64)> = Segment(0, 5, 6, 1).points().collect();
+        assert_eq!(points, vec![(0, 5), (3, 3), (6, 1)]);
+        assert_eq!(Segment(2, 2, 2, 2).points().count(), 1);
+        assert_eq!(Segment(100000, 0, 0, 300000).points().count(), 100001);
+
+        let segments = Day5::parse("0,0 -> 2,0\n0,0 -> 2,1\n2,1 -> 2,0\n").unwrap();
+        assert_eq!(
+            heatmap_pgm(&coverage(&segments, true), HEATMAP_SIDE),
+            "P2\n3 2\n2\n2 1 2\n0 0 2\n"
+        );
+        assert_eq!(
+            heatmap_pgm(&coverage(&segments, false), HEATMAP_SIDE),
+            "P2\n3 2\n2\n1 1 2\n0 0 1\n"
+        );
+    }
+
+    #[test]
+    fn test_heatmap_limits() {
+        let segments = Day5::parse("0,0 -> 2,0\n0,0 -> 2,1\n2,1 -> 2,0\n").unwrap();
+        // blocks of 2 by 2 points show their most covered point
+        assert_eq!(
+            heatmap_pgm(&coverage(&segments, true), 2),
+            "P2\n2 1\n2\n2 2\n"
+        );
+        assert_eq!(
+            heatmap_pgm(&HashMap::new(), HEATMAP_SIDE),
+            "P2\n1 1\n1\n0\n"
+        );
+
+        let far = Day5::parse(&format!(
+            "{0},{0} -> {0},{0}\n{1},{1} -> {1},{1}\n",
+            i64::MIN,
+            i64::MAX
+        ))
+        .unwrap();
+        assert_eq!(
+            heatmap_pgm(&coverage(&far, true), 2),
+            "P2\n2 2\n1\n1 0\n0 1\n"
+        );
+        assert!(Day5::parse(&format!("0,0 -> {},0\n", i64::MAX)).is_ok());
+        assert!(Day5::parse(&format!("-1,0 -> {},0\n", i64::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_sweep_count() {
+        let input = Day5::parse(&fs::read_to_string("inputs/day5.txt").unwrap()).unwrap();
+        // segments at other angles, overlapping each other, crossing at and between points
+        let mut segments = Day5::parse(
+            "0,0 -> 12,8\n3,2 -> 9,6\n0,8 -> 12,0\n6,0 -> 6,9\n1,1 -> 1,1\n\
+             0,4 -> 12,4\n12,4 -> 0,4\n2,9 -> 9,2\n6,4 -> 6,4\n0,3 -> 7,3\n",
+        )
+        .unwrap();
+        segments.extend(input);
+
+        for count_diagonals in [false, true] {
+            let map = coverage(&segments, count_diagonals);
+            for k in 1..=4 {
+                assert_eq!(
+                    sweep_count(&segments, count_diagonals, k),
+                    map.values().filter(|v| **v >= k).count(),
+                    "k = {}, diagonals: {}",
+                    k,
+                    count_diagonals
+                );
+            }
+        }
     }
 }
@@ -1,65 +1,328 @@
-use std::fs;
+use crate::error::{parse_token, ParseError};
+use crate::solution::{Answer, Solution};
+use num_bigint::BigUint;
+use std::fmt::Display;
 
-type LanternPopulation = [u64; 9];
+pub struct Day6;
 
-fn load_population(definition: &str) -> LanternPopulation {
+pub type LanternPopulation = [u64; 9];
+
+fn load_population(definition: &str) -> Result<LanternPopulation, ParseError> {
     let mut population: LanternPopulation = [0; 9];
 
-    for fish in definition
-        .split(',')
-        .map(|s| s.parse::<usize>().expect("Incorrect definition format"))
-    {
-        population[fish] += 1;
+    for s in definition.split(',') {
+        match parse_token::<usize>(definition, s, "timer between 0 and 8")? {
+            fish @ 0..=8 => population[fish] += 1,
+            _ => return Err(ParseError::at(definition, s, "timer between 0 and 8")),
+        }
+    }
+
+    Ok(population)
+}
+
+/// Numbers the population can be counted in.
+pub trait Count: Clone {
+    fn from_u64(n: u64) -> Self;
+    fn add(&self, other: &Self) -> Self;
+    fn mul(&self, other: &Self) -> Self;
+}
+
+impl Count for u64 {
+    fn from_u64(n: u64) -> u64 {
+        n
+    }
+
+    fn add(&self, other: &u64) -> u64 {
+        self + other
+    }
+
+    fn mul(&self, other: &u64) -> u64 {
+        self * other
+    }
+}
+
+/// Exact counts for any number of days, though their size grows linearly with the days.
+impl Count for BigUint {
+    fn from_u64(n: u64) -> BigUint {
+        BigUint::from(n)
+    }
+
+    fn add(&self, other: &BigUint) -> BigUint {
+        self + other
+    }
+
+    fn mul(&self, other: &BigUint) -> BigUint {
+        self * other
+    }
+}
+
+/// Count modulo `M`, which keeps the size of the counts constant for huge horizons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Modular<const M: u64>(pub u64);
+
+impl<const M: u64> Count for Modular<M> {
+    fn from_u64(n: u64) -> Modular<M> {
+        Modular(n % M)
+    }
+
+    fn add(&self, other: &Modular<M>) -> Modular<M> {
+        Modular(((self.0 as u128 + other.0 as u128) % M as u128) as u64)
     }
 
-    population
+    fn mul(&self, other: &Modular<M>) -> Modular<M> {
+        Modular((self.0 as u128 * other.0 as u128 % M as u128) as u64)
+    }
 }
 
-fn next_day(population: LanternPopulation) -> LanternPopulation {
-    [
-        population[1],
-        population[2],
-        population[3],
-        population[4],
-        population[5],
-        population[6],
-        population[7] + population[0],
-        population[8],
-        population[0],
-    ]
+type Matrix<T> = Vec<Vec<T>>;
+
+fn mat_mul<T: Count>(a: &Matrix<T>, b: &Matrix<T>) -> Matrix<T> {
+    let n = a.len();
+
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| (0..n).fold(T::from_u64(0), |sum, k| sum.add(&a[i][k].mul(&b[k][j]))))
+                .collect()
+        })
+        .collect()
+}
+
+/// Raises the matrix to the given power by repeated squaring.
+fn mat_pow<T: Count>(mut base: Matrix<T>, mut exp: u64) -> Matrix<T> {
+    let n = base.len();
+    let mut result: Matrix<T> = (0..n)
+        .map(|i| (0..n).map(|j| T::from_u64((i == j) as u64)).collect())
+        .collect();
+
+    while exp > 0 {
+        if exp % 2 == 1 {
+            result = mat_mul(&result, &base);
+        }
+        base = mat_mul(&base, &base);
+        exp /= 2;
+    }
+
+    result
+}
+
+/// Life cycle of the lanternfish, the default one is the one of the puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lifecycle {
+    /// Timer of a fish after it spawns.
+    pub reset: usize,
+    /// Timer of a newborn fish.
+    pub newborn: usize,
+    /// Age in days at which the fish die, they live forever without it.
+    pub lifespan: Option<usize>,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Lifecycle {
+        Lifecycle {
+            reset: 6,
+            newborn: 8,
+            lifespan: None,
+        }
+    }
+}
+
+impl Lifecycle {
+    /// Number of distinct timers, large enough for the timers of the input as well.
+    fn timers(&self) -> usize {
+        self.reset.max(self.newborn).max(8) + 1
+    }
+
+    /// Number of states of a fish, the fish are told apart by their timer and, if they can
+    /// die, by their age. State `age * timers + timer` is stored at the same index.
+    fn states(&self) -> usize {
+        self.timers() * self.lifespan.unwrap_or(1)
+    }
+
+    fn state(&self, age: usize, timer: usize) -> Option<usize> {
+        match self.lifespan {
+            Some(lifespan) if age >= lifespan => None,
+            Some(_) => Some(age * self.timers() + timer),
+            None => Some(timer),
+        }
+    }
+
+    /// States the fish in the given state turns into on the next day, itself unless it dies
+    /// and its newborn if it spawns. A fish spawning on the day it dies leaves the newborn.
+    fn successors(&self, state: usize) -> impl Iterator<Item = usize> {
+        let (age, timer) = (state / self.timers(), state % self.timers());
+        let (timer, newborn) = match timer {
+            0 => (self.reset, self.state(0, self.newborn)),
+            _ => (timer - 1, None),
+        };
+
+        self.state(age + 1, timer).into_iter().chain(newborn)
+    }
+
+    /// Counts of the fish in each of the states. The fish of the input are taken to be in
+    /// their first cycle, so a fish with timer `t` is `newborn - t` days old.
+    fn counts<T: Count>(&self, population: &LanternPopulation) -> Vec<T> {
+        let mut counts = vec![T::from_u64(0); self.states()];
+
+        for (timer, count) in population.iter().enumerate() {
+            if let Some(state) = self.state(self.newborn.saturating_sub(timer), timer) {
+                counts[state] = counts[state].add(&T::from_u64(*count));
+            }
+        }
+
+        counts
+    }
+
+    fn next_day<T: Count>(&self, counts: &[T]) -> Vec<T> {
+        let mut next = vec![T::from_u64(0); self.states()];
+
+        for (state, count) in counts.iter().enumerate() {
+            for successor in self.successors(state) {
+                next[successor] = next[successor].add(count);
+            }
+        }
+
+        next
+    }
+
+    /// Matrix of a single day, the fish in state `i` on the next day come from the fish in
+    /// state `j` on the previous one `transition[i][j]` times.
+    fn transition<T: Count>(&self) -> Matrix<T> {
+        let mut matrix = vec![vec![T::from_u64(0); self.states()]; self.states()];
+
+        let edges = (0..self.states()).flat_map(|state| {
+            self.successors(state)
+                .map(move |successor| (state, successor))
+        });
+
+        for (state, successor) in edges {
+            matrix[successor][state] = matrix[successor][state].add(&T::from_u64(1));
+        }
+
+        matrix
+    }
+
+    /// Total population after the given number of days, in logarithmic time. The time is
+    /// cubic in the number of states though, so it is best suited for immortal fish.
+    pub fn project<T: Count>(&self, population: &LanternPopulation, days: u64) -> T {
+        let matrix: Matrix<T> = mat_pow(self.transition(), days);
+        let counts: Vec<T> = self.counts(population);
+
+        matrix
+            .iter()
+            .flat_map(|row| row.iter().zip(&counts))
+            .fold(T::from_u64(0), |sum, (m, count)| sum.add(&m.mul(count)))
+    }
+
+    /// Total population on each day, starting with the initial one, up to the given day.
+    pub fn series<T: Count>(&self, population: &LanternPopulation, days: usize) -> Vec<T> {
+        let mut counts: Vec<T> = self.counts(population);
+        let mut series = Vec::with_capacity(days + 1);
+
+        for day in 0..=days {
+            if day > 0 {
+                counts = self.next_day(&counts);
+            }
+            series.push(counts.iter().fold(T::from_u64(0), |sum, c| sum.add(c)));
+        }
+
+        series
+    }
 }
 
-fn population_total(population: LanternPopulation) -> u64 {
-    population.iter().sum()
+/// Total population after the given number of days with the life cycle of the puzzle.
+pub fn project<T: Count>(population: &LanternPopulation, days: u64) -> T {
+    Lifecycle::default().project(population, days)
 }
 
-fn general_solution(days: u16) -> String {
-    // initialize state
-    let mut input_str = fs::read_to_string("inputs/day6.txt").expect("Could not read file");
-    input_str.pop(); // remove newline
-    let mut population = load_population(&input_str[..]);
-    // iterate
-    for _ in 0..days {
-        population = next_day(population);
-    }
-    // return sum as string
-    population_total(population).to_string()
+/// Population on each day of each of the named scenarios as CSV, one line per day.
+pub fn series_csv<T: Display>(scenarios: &[(&str, Vec<T>)]) -> String {
+    let mut csv = String::from("scenario,day,population\n");
+
+    for (name, series) in scenarios {
+        for (day, total) in series.iter().enumerate() {
+            csv += &format!("{},{},{}\n", name, day, total);
+        }
+    }
+
+    csv
 }
 
-pub fn ex1() -> String {
-    general_solution(80)
+fn general_solution(population: &LanternPopulation, days: u64) -> String {
+    project::<u64>(population, days).to_string()
 }
 
-pub fn ex2() -> String {
-    general_solution(256)
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+    type Input = LanternPopulation;
+
+    fn parse(input: &str) -> Result<LanternPopulation, ParseError> {
+        load_population(input.trim_end())
+    }
+
+    fn part1(population: &LanternPopulation) -> Answer {
+        Ok(general_solution(population, 80))
+    }
+
+    fn part2(population: &LanternPopulation) -> Answer {
+        Ok(general_solution(population, 256))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::{project, Day6, Lifecycle, Modular};
+    use crate::solution::Solution;
+    use num_bigint::BigUint;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "345387");
-        assert_eq!(ex2(), "1574445493136");
+        let input = Day6::parse(&fs::read_to_string("inputs/day6.txt").unwrap()).unwrap();
+        assert_eq!(Day6::part1(&input).unwrap(), "345387");
+        assert_eq!(Day6::part2(&input).unwrap(), "1574445493136");
+    }
+
+    #[test]
+    fn test_projection() {
+        const P: u64 = 1_000_000_007;
+        let population = Day6::parse("3,4,3,1,2\n").unwrap();
+
+        assert_eq!(project::<u64>(&population, 18), 26);
+        assert_eq!(project::<u64>(&population, 0), 5);
+        let exact = project::<BigUint>(&population, 1000);
+        assert!(exact.bits() > u64::BITS as u64);
+        assert_eq!(
+            project::<Modular<P>>(&population, 1000),
+            Modular((exact % P).try_into().unwrap())
+        );
+        // the horizon would be out of reach day by day
+        assert!(project::<Modular<P>>(&population, 1_000_000_000_000).0 < P);
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let population = Day6::parse("3,4,3,1,2\n").unwrap();
+        let puzzle = Lifecycle::default();
+        let series: Vec<u64> = puzzle.series(&population, 18);
+        assert_eq!(series[0], 5);
+        assert_eq!(series[18], 26);
+
+        let single = Day6::parse("0\n").unwrap();
+        let short = Lifecycle {
+            reset: 2,
+            newborn: 2,
+            lifespan: None,
+        };
+        assert_eq!(short.series::<u64>(&single, 4), vec![1, 2, 2, 2, 4]);
+        // the fish of the input is 8 days old, its newborn outlives it
+        let mortal = Lifecycle {
+            lifespan: Some(10),
+            ..puzzle
+        };
+        assert_eq!(mortal.series::<u64>(&single, 2), vec![1, 2, 1]);
+        assert_eq!(
+            mortal.project::<u64>(&population, 40),
+            mortal.series(&population, 40)[40]
+        );
     }
 }
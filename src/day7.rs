@@ -1,62 +1,488 @@
-use std::fs;
+use crate::error::{parse_token, ParseError};
+use crate::solution::{Answer, Solution};
+use std::cmp::Ordering;
+use std::ops::{Range, RangeInclusive};
 
-fn ex1_loss(positions: &Vec<i32>, current: i32) -> i32 {
-    positions.iter().map(|p| (current - p).abs()).sum()
+pub struct Day7;
+
+/// Crab at a position, whose fuel is multiplied by its weight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crab {
+    pub position: i32,
+    pub weight: u64,
+}
+
+impl From<i32> for Crab {
+    fn from(position: i32) -> Crab {
+        Crab {
+            position,
+            weight: 1,
+        }
+    }
+}
+
+/// Fuel a crab spends to move over a distance, the total cost of an alignment is the sum
+/// over all of the crabs.
+pub trait Cost {
+    fn cost(&self, distance: u64) -> u64;
+
+    /// Alignment position with the least total cost of the weighted crabs and the cost
+    /// itself. Every position between the outermost crabs is tried, so this works for any
+    /// cost function.
+    fn weighted_optimum(&self, crabs: &[Crab]) -> (i32, u64) {
+        let min = crabs.iter().map(|crab| crab.position).min().unwrap_or(0);
+        let max = crabs.iter().map(|crab| crab.position).max().unwrap_or(0);
+
+        best_of(crabs, self, min..=max)
+    }
+
+    /// Alignment position with the least total cost and the cost itself.
+    fn optimum(&self, positions: &[i32]) -> (i32, u64) {
+        let crabs: Vec<Crab> = positions.iter().map(|&p| Crab::from(p)).collect();
+        self.weighted_optimum(&crabs)
+    }
+
+    /// Optimum of every run of the crabs sorted by position, `runs[i][j - i - 1]` being the
+    /// one of `sorted[i..j]`. The costs of the positions from the start of a run onwards are
+    /// accumulated as the run grows, so this takes `O(n^2)` sums over the positions.
+    fn run_optima(&self, sorted: &[Crab]) -> Vec<Vec<(i32, u64)>> {
+        let Some(last) = sorted.last() else {
+            return Vec::new();
+        };
+
+        (0..sorted.len())
+            .map(|i| {
+                let start = sorted[i].position;
+                // totals[t] is the cost of aligning the run at start + t
+                let mut totals = vec![0; last.position.abs_diff(start) as usize + 1];
+                sorted[i..]
+                    .iter()
+                    .map(|crab| {
+                        for (t, total) in totals.iter_mut().enumerate() {
+                            let target = start + t as i32;
+                            *total +=
+                                crab.weight * self.cost(target.abs_diff(crab.position) as u64);
+                        }
+                        totals[..=crab.position.abs_diff(start) as usize]
+                            .iter()
+                            .enumerate()
+                            .min_by_key(|(_, total)| **total)
+                            .map(|(t, total)| (start + t as i32, *total))
+                            .unwrap_or((start, 0))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Any function of the distance can be used as a cost.
+impl<F: Fn(u64) -> u64> Cost for F {
+    fn cost(&self, distance: u64) -> u64 {
+        self(distance)
+    }
+}
+
+/// Every step costs one unit of fuel, as in the first part.
+pub struct Linear;
+
+impl Cost for Linear {
+    fn cost(&self, distance: u64) -> u64 {
+        distance
+    }
+
+    /// Any weighted median minimises the weighted sum of absolute distances.
+    fn weighted_optimum(&self, crabs: &[Crab]) -> (i32, u64) {
+        let mut sorted = crabs.to_vec();
+        sorted.sort_unstable_by_key(|crab| crab.position);
+        let half: u64 = sorted
+            .iter()
+            .map(|crab| crab.weight)
+            .sum::<u64>()
+            .div_ceil(2);
+        let median = sorted
+            .iter()
+            .scan(0, |weight, crab| {
+                *weight += crab.weight;
+                Some((*weight, crab.position))
+            })
+            .find(|(weight, _)| *weight >= half)
+            .map_or(0, |(_, position)| position);
+
+        best_of(crabs, self, median..=median)
+    }
+
+    /// The weighted median of a run only moves right as the run grows, so it is followed
+    /// along and the cost at it comes from the prefix sums in constant time.
+    fn run_optima(&self, sorted: &[Crab]) -> Vec<Vec<(i32, u64)>> {
+        let moments = Moments::new(sorted);
+        (0..sorted.len())
+            .map(|i| {
+                let mut median = i;
+                (i + 1..=sorted.len())
+                    .map(|j| {
+                        let half = (moments.weight(i..j) + 1) / 2;
+                        while moments.weight(i..median + 1) < half {
+                            median += 1;
+                        }
+                        let target = sorted[median].position;
+                        (target, moments.distance(i..j, median, target) as u64)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Each step costs one more unit than the previous one, as in the second part.
+pub struct Triangular;
+
+impl Cost for Triangular {
+    fn cost(&self, distance: u64) -> u64 {
+        distance * (distance + 1) / 2
+    }
+
+    /// The derivative of the total cost is `w * (x - mean)` give or take `w / 2`, where `w`
+    /// is the total weight and `mean` the weighted mean, so the optimum lies within half a
+    /// step of the mean and only the integers around it are tried.
+    fn weighted_optimum(&self, crabs: &[Crab]) -> (i32, u64) {
+        let w: i128 = crabs.iter().map(|crab| crab.weight as i128).sum();
+        let sum: i128 = crabs
+            .iter()
+            .map(|crab| crab.position as i128 * crab.weight as i128)
+            .sum();
+
+        best_of(crabs, self, around_mean(w, sum))
+    }
+
+    /// The candidates around the mean of a run come from the prefix sums, and the cost of
+    /// each from the prefix sums split at the candidate, found by binary search.
+    fn run_optima(&self, sorted: &[Crab]) -> Vec<Vec<(i32, u64)>> {
+        let moments = Moments::new(sorted);
+        (0..sorted.len())
+            .map(|i| {
+                (i + 1..=sorted.len())
+                    .map(|j| {
+                        around_mean(moments.weight(i..j), moments.first(i..j))
+                            .map(|target| {
+                                let split =
+                                    i + sorted[i..j].partition_point(|crab| crab.position < target);
+                                let squares = moments.squared_distance(i..j, target);
+                                let total = (squares + moments.distance(i..j, split, target)) / 2;
+                                (target, total as u64)
+                            })
+                            .min_by_key(|(_, total)| *total)
+                            .unwrap_or((0, 0))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Integers within half a step of the mean `sum / w`, the only candidates for the optimum of
+/// the triangular cost, or just zero without any weight.
+fn around_mean(w: i128, sum: i128) -> RangeInclusive<i32> {
+    if w == 0 {
+        return 0..=0;
+    }
+    // floor and ceiling of mean - 1/2 and mean + 1/2
+    let low = (2 * sum - w).div_euclid(2 * w);
+    let high = -(-(2 * sum + w)).div_euclid(2 * w);
+
+    low as i32..=high as i32
+}
+
+/// Prefix sums of the weights and of the weighted positions and squared positions of crabs
+/// sorted by position, which give the total distances of any run of them to a target.
+struct Moments {
+    weights: Vec<i128>,
+    firsts: Vec<i128>,
+    seconds: Vec<i128>,
+}
+
+impl Moments {
+    fn new(sorted: &[Crab]) -> Moments {
+        let mut moments = Moments {
+            weights: vec![0],
+            firsts: vec![0],
+            seconds: vec![0],
+        };
+        for crab in sorted {
+            let (w, x) = (crab.weight as i128, crab.position as i128);
+            moments.weights.push(moments.weights.last().unwrap() + w);
+            moments.firsts.push(moments.firsts.last().unwrap() + w * x);
+            moments
+                .seconds
+                .push(moments.seconds.last().unwrap() + w * x * x);
+        }
+
+        moments
+    }
+
+    fn weight(&self, run: Range<usize>) -> i128 {
+        self.weights[run.end] - self.weights[run.start]
+    }
+
+    fn first(&self, run: Range<usize>) -> i128 {
+        self.firsts[run.end] - self.firsts[run.start]
+    }
+
+    /// Weighted sum of the distances of the run to `target`, where the crabs before `split`
+    /// are at or left of the target and the rest at or right of it.
+    fn distance(&self, run: Range<usize>, split: usize, target: i32) -> i128 {
+        let (below, above) = (run.start..split, split..run.end);
+        let t = target as i128;
+
+        t * self.weight(below.clone()) - self.first(below) + self.first(above.clone())
+            - t * self.weight(above)
+    }
+
+    /// Weighted sum of the squared distances of the run to `target`.
+    fn squared_distance(&self, run: Range<usize>, target: i32) -> i128 {
+        let t = target as i128;
+        let second = self.seconds[run.end] - self.seconds[run.start];
+
+        t * t * self.weight(run.clone()) - 2 * t * self.first(run) + second
+    }
 }
 
-fn ex2_loss(positions: &Vec<i32>, current: i32) -> i32 {
-    fn f(k: i32) -> i32 {
-        let half: f32 = k as f32 / 2.0;
-        (half.floor() * (k + 1) as f32 + half.ceil() * (k % 2) as f32) as i32
+/// Cost function increasing and convex in the distance, which makes the total cost convex
+/// in the alignment position, so the optimum is found by ternary search.
+pub struct Convex<C>(pub C);
+
+impl<C: Cost> Cost for Convex<C> {
+    fn cost(&self, distance: u64) -> u64 {
+        self.0.cost(distance)
+    }
+
+    fn weighted_optimum(&self, crabs: &[Crab]) -> (i32, u64) {
+        let mut low = crabs.iter().map(|crab| crab.position).min().unwrap_or(0);
+        let mut high = crabs.iter().map(|crab| crab.position).max().unwrap_or(0);
+
+        while high - low > 2 {
+            let third = (high - low) / 3;
+            let (left, right) = (low + third, high - third);
+            match weighted_cost(crabs, self, left).cmp(&weighted_cost(crabs, self, right)) {
+                Ordering::Less => high = right - 1,
+                Ordering::Greater => low = left + 1,
+                // a convex function has a minimum between two points with equal values
+                Ordering::Equal => (low, high) = (left, right),
+            }
+        }
+
+        best_of(crabs, self, low..=high)
+    }
+}
+
+/// Total cost of aligning all of the crabs at `target`.
+pub fn total_cost<C: Cost + ?Sized>(positions: &[i32], cost: &C, target: i32) -> u64 {
+    positions
+        .iter()
+        .map(|p| cost.cost(target.abs_diff(*p) as u64))
+        .sum()
+}
+
+/// Total cost of aligning all of the weighted crabs at `target`.
+pub fn weighted_cost<C: Cost + ?Sized>(crabs: &[Crab], cost: &C, target: i32) -> u64 {
+    crabs
+        .iter()
+        .map(|crab| crab.weight * cost.cost(target.abs_diff(crab.position) as u64))
+        .sum()
+}
+
+/// The candidate with the least total cost, the leftmost one on ties.
+fn best_of<C: Cost + ?Sized>(
+    crabs: &[Crab],
+    cost: &C,
+    candidates: RangeInclusive<i32>,
+) -> (i32, u64) {
+    candidates
+        .map(|target| (target, weighted_cost(crabs, cost, target)))
+        .min_by_key(|(_, total)| *total)
+        .unwrap_or((0, 0))
+}
+
+/// Splits the fleet between at most `k` alignment points with the least total cost, returns
+/// the points from left to right and the cost, or `None` if there are crabs but no points.
+///
+/// With a cost increasing in the distance, every point gathers a contiguous run of the crabs
+/// sorted by position, so the least cost of the first `j` crabs in `m` runs is found by
+/// dynamic programming over the end of the previous run in `O(k * n^2)` steps, on top of the
+/// optima of the runs given by `Cost::run_optima`.
+pub fn align_fleet<C: Cost>(crabs: &[Crab], cost: &C, k: usize) -> Option<(Vec<i32>, u64)> {
+    let mut crabs = crabs.to_vec();
+    crabs.sort_unstable_by_key(|crab| crab.position);
+    let n = crabs.len();
+    if n == 0 {
+        return Some((Vec::new(), 0));
+    } else if k == 0 {
+        return None;
+    }
+
+    // runs[i][j - i - 1] is the optimum of the crabs i..j
+    let runs = cost.run_optima(&crabs);
+    // layers[m][j] is the least cost of the first j crabs in m + 1 runs and the start of
+    // the last run
+    let mut layers: Vec<Vec<Option<(u64, usize)>>> = vec![(0..=n)
+        .map(|j| (j > 0).then(|| (runs[0][j - 1].1, 0)))
+        .collect()];
+
+    for m in 1..k.min(n) {
+        let previous = &layers[m - 1];
+        let layer = (0..=n)
+            .map(|j| {
+                (m..j)
+                    .filter_map(|i| previous[i].map(|(total, _)| (total + runs[i][j - i - 1].1, i)))
+                    .min()
+            })
+            .collect();
+        layers.push(layer);
+    }
+
+    let (mut m, (total, _)) = layers
+        .iter()
+        .enumerate()
+        .filter_map(|(m, layer)| layer[n].map(|best| (m, best)))
+        .min_by_key(|(_, (total, _))| *total)?;
+    let mut points = Vec::with_capacity(m + 1);
+    let mut j = n;
+    loop {
+        let (_, i) = layers[m][j]?;
+        points.push(runs[i][j - i - 1].0);
+        if m == 0 {
+            break;
+        }
+        (m, j) = (m - 1, i);
     }
+    points.reverse();
 
-    positions.iter().map(|p| f((current - p).abs())).sum()
+    Some((points, total))
 }
 
-fn load_positions(definition: &str) -> Vec<i32> {
+fn load_position(definition: &str, token: &str) -> Result<i32, ParseError> {
+    parse_token(definition, token, "crab position")
+}
+
+fn load_positions(definition: &str) -> Result<Vec<i32>, ParseError> {
+    definition
+        .split(',')
+        .map(|s| load_position(definition, s))
+        .collect()
+}
+
+/// Loads crabs in the form of the puzzle input, where each position may be followed by
+/// `*weight`. Crabs without a weight have the weight of one.
+pub fn load_crabs(definition: &str) -> Result<Vec<Crab>, ParseError> {
+    let definition = definition.trim_end();
     definition
         .split(',')
-        .map(|s| s.parse::<i32>().expect("Incorrect definition format"))
+        .map(|s| match s.split_once('*') {
+            Some((position, weight)) => Ok(Crab {
+                position: load_position(definition, position)?,
+                weight: parse_token(definition, weight, "crab weight")?,
+            }),
+            None => load_position(definition, s).map(Crab::from),
+        })
         .collect()
 }
 
-fn general_solution(loss_fn: fn(&Vec<i32>, i32) -> i32) -> String {
-    // initialize state
-    let mut input_str = fs::read_to_string("inputs/day7.txt").expect("Could not read file");
-    input_str.pop(); // remove newline
-    let positions = load_positions(&input_str[..]);
+fn general_solution<C: Cost>(positions: &[i32], cost: C) -> String {
+    cost.optimum(positions).1.to_string()
+}
 
-    let mut current: i32 = positions.iter().sum::<i32>() / positions.iter().count() as i32;
-    let mut current_loss = loss_fn(&positions, current);
-    let sgn: i32 = if loss_fn(&positions, current - 1) < loss_fn(&positions, current) {
-        -1
-    } else {
-        1
-    };
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+    type Input = Vec<i32>;
 
-    let mut new_loss = loss_fn(&positions, current + sgn);
-    while new_loss < current_loss {
-        current += sgn;
-        current_loss = new_loss;
-        new_loss = loss_fn(&positions, current + sgn);
+    fn parse(input: &str) -> Result<Vec<i32>, ParseError> {
+        load_positions(input.trim_end())
     }
 
-    loss_fn(&positions, current).to_string()
-}
+    fn part1(positions: &Vec<i32>) -> Answer {
+        Ok(general_solution(positions, Linear))
+    }
 
-pub fn ex1() -> String {
-    general_solution(ex1_loss)
-}
-pub fn ex2() -> String {
-    general_solution(ex2_loss)
+    fn part2(positions: &Vec<i32>) -> Answer {
+        Ok(general_solution(positions, Triangular))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::{align_fleet, load_crabs, Convex, Cost, Crab, Day7, Linear, Triangular};
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "323647");
-        assert_eq!(ex2(), "87640209");
+        let input = Day7::parse(&fs::read_to_string("inputs/day7.txt").unwrap()).unwrap();
+        assert_eq!(Day7::part1(&input).unwrap(), "323647");
+        assert_eq!(Day7::part2(&input).unwrap(), "87640209");
+    }
+
+    #[test]
+    fn test_cost_functions() {
+        let positions = Day7::parse("16,1,2,0,4,2,7,1,2,14\n").unwrap();
+        let triangular = |d: u64| d * (d + 1) / 2;
+
+        assert_eq!(Linear.optimum(&positions), (2, 37));
+        assert_eq!(Triangular.optimum(&positions), (5, 168));
+        assert_eq!(Convex(Linear).optimum(&positions), (2, 37));
+        assert_eq!(Convex(triangular).optimum(&positions), (5, 168));
+        assert_eq!(triangular.optimum(&positions), (5, 168));
+        // crabs only pay for the distance above a multiple of 5, which is not convex
+        let periodic = |d: u64| d % 5;
+        assert_eq!(periodic.optimum(&positions), (1, 11));
+        assert_eq!(Triangular.optimum(&[]), (0, 0));
+    }
+
+    #[test]
+    fn test_weighted_fleet() {
+        let crabs = load_crabs("16,1,2,0,4,2,7,1,2,14\n").unwrap();
+        assert_eq!(align_fleet(&crabs, &Linear, 1), Some((vec![2], 37)));
+        assert_eq!(align_fleet(&crabs, &Triangular, 1), Some((vec![5], 168)));
+        assert_eq!(align_fleet(&crabs, &Linear, 2), Some((vec![2, 14], 13)));
+        assert_eq!(align_fleet(&crabs, &Linear, 0), None);
+        assert_eq!(align_fleet(&crabs, &Linear, 20).unwrap().1, 0);
+
+        let heavy = load_crabs("0,10*3,11").unwrap();
+        assert_eq!(
+            heavy[1],
+            Crab {
+                position: 10,
+                weight: 3
+            }
+        );
+        assert_eq!(Linear.weighted_optimum(&heavy), (10, 11));
+        assert_eq!(
+            Triangular.weighted_optimum(&heavy),
+            Convex(Triangular).weighted_optimum(&heavy)
+        );
+        assert!(load_crabs("1,2*x").is_err());
+    }
+
+    #[test]
+    fn test_run_optima() {
+        let mut crabs = load_crabs("16,1*2,2,0*0,4,2*5,7,1,2*3,14*2,-3").unwrap();
+        crabs.sort_unstable_by_key(|crab| crab.position);
+        let linear = |d: u64| d;
+        let triangular = |d: u64| d * (d + 1) / 2;
+        let costs = |runs: Vec<Vec<(i32, u64)>>| -> Vec<Vec<u64>> {
+            runs.into_iter()
+                .map(|run| run.into_iter().map(|(_, total)| total).collect())
+                .collect()
+        };
+
+        // the prefix sums agree with summing the costs at every position
+        assert_eq!(
+            costs(Linear.run_optima(&crabs)),
+            costs(linear.run_optima(&crabs))
+        );
+        assert_eq!(Triangular.run_optima(&crabs), triangular.run_optima(&crabs));
+        for (i, runs) in Linear.run_optima(&crabs).into_iter().enumerate() {
+            for (j, run) in (i + 1..).zip(runs) {
+                assert_eq!(run, Linear.weighted_optimum(&crabs[i..j]));
+            }
+        }
     }
 }
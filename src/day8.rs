@@ -1,217 +1,425 @@
+use crate::error::{parse_lines, ParseError};
+use crate::solution::{Answer, Solution};
 use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
-use std::fs;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
 
-type SegmentBlock = HashSet<u8>;
+pub struct Day8;
 
-type LeftEncoded = [SegmentBlock; 10];
-type RightEncoded = [SegmentBlock; 4];
-type Solution = HashMap<u8, u8>;
+pub type SegmentBlock = HashSet<u8>;
 
-const PROCESS_LINE_ERR_MSG: &'static str = "Incorrect line format";
+pub type LeftEncoded = [SegmentBlock; 10];
+pub type RightEncoded = [SegmentBlock; 4];
 
-fn process_segment_block<'a>(segment: &'a str) -> SegmentBlock {
+fn process_segment_block(line: &str, segment: &str) -> Result<SegmentBlock, ParseError> {
     let mut block = SegmentBlock::new();
 
-    for k in segment.chars().map(|c| c as usize - 'a' as usize) {
-        block.insert(k as u8);
+    for (k, c) in segment.char_indices() {
+        if !('a'..='g').contains(&c) {
+            return Err(ParseError::at(
+                line,
+                &segment[k..k + c.len_utf8()],
+                "segment between 'a' and 'g'",
+            ));
+        }
+        block.insert(c as u8 - b'a');
     }
 
-    block
+    Ok(block)
 }
 
-fn process_line<'a>(line: &'a str) -> (LeftEncoded, RightEncoded) {
-    if let [code_str, input_str] = line.split('|').collect::<Vec<&str>>()[..2] {
-        return (
-            code_str
-                .split_ascii_whitespace()
-                .map(|s| process_segment_block(s))
-                .collect::<Vec<SegmentBlock>>()
-                .try_into()
-                .expect(PROCESS_LINE_ERR_MSG),
-            input_str
-                .split_ascii_whitespace()
-                .map(|s| process_segment_block(s))
-                .collect::<Vec<SegmentBlock>>()
-                .try_into()
-                .expect(PROCESS_LINE_ERR_MSG),
-        );
-    }
-    panic!();
+fn process_blocks<const N: usize>(
+    line: &str,
+    blocks: &str,
+    expected: &str,
+) -> Result<[SegmentBlock; N], ParseError> {
+    blocks
+        .split_ascii_whitespace()
+        .map(|segment| process_segment_block(line, segment))
+        .collect::<Result<Vec<SegmentBlock>, ParseError>>()?
+        .try_into()
+        .map_err(|_| ParseError::at(line, blocks, expected))
 }
 
-fn solve(code: LeftEncoded) -> Solution {
-    let mut solution = HashMap::new();
+fn process_line(line: &str) -> Result<(LeftEncoded, RightEncoded), ParseError> {
+    let (code_str, input_str) = line
+        .split_once('|')
+        .ok_or_else(|| ParseError::at_end(line, "'|' separator"))?;
 
-    // Get segment blocks for 7 and 1. 7 has length 3 while 1 has length 2. The segment that occurs in 7
-    // but not in 1 identifies the top center segment.
-    let seven_segment_block = code.iter().filter(|b| b.len() == 3).next().unwrap();
-    let one_segment_block = code.iter().filter(|b| b.len() == 2).next().unwrap();
-    let top_center_segment = seven_segment_block
-        .difference(&one_segment_block)
-        .next()
-        .unwrap();
-    solution.insert(*top_center_segment, 0);
-
-    // Consider the upper left segment and the middle center segment for the number 4. For the group of numbers
-    // with 5 segments (2, 3, and 5), upper left segment will be included once while the middle center segment
-    // will be included thrice. Fill in both.
-    let four_segment_block = code.iter().filter(|b| b.len() == 4).next().unwrap();
-    let relevant_segments: Vec<&u8> = four_segment_block.difference(&one_segment_block).collect();
-    let segment_a = *relevant_segments[0];
-    let segment_b = *relevant_segments[1];
-    let segment_a_count = code
-        .iter()
-        .filter(|b| b.len() == 5 && b.contains(&segment_a))
-        .count();
-    if segment_a_count == 1 {
-        solution.insert(segment_a, 1);
-        solution.insert(segment_b, 3);
-    } else {
-        solution.insert(segment_a, 3);
-        solution.insert(segment_b, 1);
-    }
-
-    // For the group of numbers with 5 segments (2, 3, and 5), only 5 will contain the newly discovered
-    // upper left segment. Use this information to fill-in lower right segment and then upper right segment.
-    let upper_left_segment = if segment_a_count == 1 {
-        segment_a
-    } else {
-        segment_b
-    };
-    let mut five_segment_block: SegmentBlock = code
-        .iter()
-        .cloned()
-        .filter(|b| b.len() == 5 && b.contains(&upper_left_segment))
-        .next()
-        .unwrap();
-    for k in solution.keys() {
-        five_segment_block.remove(k);
-    }
-    let (segment_a, segment_b) = five_segment_block.iter().next_tuple().unwrap();
-    let segment_a_count = code
-        .iter()
-        .filter(|b| b.len() == 5 && b.contains(&segment_a))
-        .count();
-    let mut segment_two: u8 = 0;
-    let segment_five: u8;
-    if segment_a_count == 2 {
-        segment_five = *segment_a;
-        for segment in one_segment_block {
-            if *segment != *segment_a {
-                segment_two = *segment;
+    Ok((
+        process_blocks(line, code_str, "10 segment blocks")?,
+        process_blocks(line, input_str, "4 segment blocks")?,
+    ))
+}
+
+/// Set of segments or wires, bit `k` stands for the segment or wire `k`.
+type Mask = u64;
+
+/// Segment lit by each of the wires, indexed by the wire.
+pub type Wiring = Vec<u8>;
+
+/// Error raised when the wiring of a display cannot be determined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// No wiring maps every pattern to a glyph of the table.
+    NoSolution,
+    /// More than one wiring maps every pattern to a glyph, two of them are given.
+    Ambiguous(Wiring, Wiring),
+    /// The pattern with the given wires does not map to any glyph under the wiring.
+    UnknownGlyph(Vec<u8>),
+    /// The display has more segments than the 64 a table supports.
+    TooManySegments(usize),
+    /// The glyph of the given symbol lights a segment the display does not have.
+    UnknownSegment { symbol: usize, segment: u8 },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::NoSolution => write!(f, "no wiring matches the patterns"),
+            DecodeError::Ambiguous(a, b) => {
+                write!(f, "wirings {:?} and {:?} both match the patterns", a, b)
             }
-        }
-    } else {
-        segment_five = *segment_b;
-        for segment in one_segment_block {
-            if *segment != *segment_b {
-                segment_two = *segment;
+            DecodeError::UnknownGlyph(wires) => write!(f, "wires {:?} form no glyph", wires),
+            DecodeError::TooManySegments(segments) => {
+                write!(f, "{} segments, at most {} supported", segments, Mask::BITS)
+            }
+            DecodeError::UnknownSegment { symbol, segment } => {
+                write!(f, "symbol {} lights unknown segment {}", symbol, segment)
             }
         }
     }
-    solution.insert(segment_five, 5);
-    solution.insert(segment_two, 2);
-
-    // For numbers 2 and 3, only 3 will contain lower right segment. Use this information to
-    // identify bottom middle segment.
-    let segment_six: u8;
-    let (segment_block_a, segment_block_b): (SegmentBlock, SegmentBlock) = code
-        .iter()
-        .cloned()
-        .filter(|b| b.len() == 5 && !b.contains(&upper_left_segment))
-        .next_tuple()
-        .unwrap();
+}
 
-    let d: HashSet<u8> = solution.keys().cloned().collect();
-    if segment_block_a.contains(&segment_five) {
-        segment_six = *segment_block_a.difference(&d).next().unwrap();
-    } else {
-        segment_six = *segment_block_b.difference(&d).next().unwrap();
+impl Error for DecodeError {}
+
+fn mask(block: &SegmentBlock) -> Mask {
+    block.iter().fold(0, |mask, k| mask | 1 << k)
+}
+
+fn bits(mask: Mask) -> impl Iterator<Item = u8> {
+    (0..Mask::BITS as u8).filter(move |k| mask & 1 << k != 0)
+}
+
+/// Display with a glyph of lit segments for each of its symbols, the symbols are numbered
+/// by their position in the table.
+#[derive(Debug, Clone)]
+pub struct GlyphTable {
+    segments: usize,
+    glyphs: Vec<Mask>,
+}
+
+impl GlyphTable {
+    /// Creates a table of a display with the given number of segments, at most 64, from the
+    /// segments lit for each of the symbols. Segments are numbered from zero.
+    pub fn new(segments: usize, glyphs: &[&[u8]]) -> Result<GlyphTable, DecodeError> {
+        if segments > Mask::BITS as usize {
+            return Err(DecodeError::TooManySegments(segments));
+        }
+        let glyphs = glyphs
+            .iter()
+            .enumerate()
+            .map(|(symbol, glyph)| {
+                glyph.iter().try_fold(0, |mask, &segment| {
+                    if (segment as usize) < segments {
+                        Ok(mask | 1 << segment)
+                    } else {
+                        Err(DecodeError::UnknownSegment { symbol, segment })
+                    }
+                })
+            })
+            .collect::<Result<Vec<Mask>, DecodeError>>()?;
+
+        Ok(GlyphTable { segments, glyphs })
     }
-    solution.insert(segment_six, 6);
 
-    // The only missing segment is the lower left one. Fill it in.
-    let mut keys: HashSet<u8> = HashSet::from([0, 1, 2, 3, 4, 5, 6]);
-    let mut values: HashSet<u8> = HashSet::from([0, 1, 2, 3, 4, 5, 6]);
-    for (k, v) in solution.iter() {
-        keys.remove(k);
-        values.remove(v);
+    /// Whether all of the wires of the pattern are connected to the display.
+    fn connected(&self, pattern: &SegmentBlock) -> bool {
+        pattern.iter().all(|&wire| (wire as usize) < self.segments)
     }
-    solution.insert(*keys.iter().next().unwrap(), *values.iter().next().unwrap());
 
-    // Return a fully filled-in solution hash map.
-    solution
-}
+    /// The digits of the standard seven-segment display, with the segments numbered from
+    /// the top one row by row, left to right.
+    pub fn seven_segment() -> GlyphTable {
+        GlyphTable::new(
+            7,
+            &[
+                &[0, 1, 2, 4, 5, 6],
+                &[2, 5],
+                &[0, 2, 3, 4, 6],
+                &[0, 2, 3, 5, 6],
+                &[1, 2, 3, 5],
+                &[0, 1, 3, 5, 6],
+                &[0, 1, 3, 4, 5, 6],
+                &[0, 2, 5],
+                &[0, 1, 2, 3, 4, 5, 6],
+                &[0, 1, 2, 3, 5, 6],
+            ],
+        )
+        .expect("seven-segment digits use the seven segments")
+    }
 
-fn decode(output: RightEncoded, solution: Solution) -> u32 {
-    let letters: HashMap<Vec<u8>, u8> = HashMap::from([
-        (vec![0, 1, 2, 4, 5, 6], 0),
-        (vec![2, 5], 1),
-        (vec![0, 2, 3, 4, 6], 2),
-        (vec![0, 2, 3, 5, 6], 3),
-        (vec![1, 2, 3, 5], 4),
-        (vec![0, 1, 3, 5, 6], 5),
-        (vec![0, 1, 3, 4, 5, 6], 6),
-        (vec![0, 2, 5], 7),
-        (vec![0, 1, 2, 3, 4, 5, 6], 8),
-        (vec![0, 1, 2, 3, 5, 6], 9),
-    ]);
-
-    let decoded_output: Vec<Vec<u8>> = output
-        .iter()
-        .map(|b| {
-            b.iter()
-                .map(|k| *solution.get(k).unwrap())
-                .sorted()
-                .collect()
+    /// Glyphs the pattern may still turn into given the segments each wire may light.
+    fn candidates<'a>(
+        &'a self,
+        pattern: Mask,
+        allowed: &'a [Mask],
+    ) -> impl Iterator<Item = Mask> + 'a {
+        self.glyphs.iter().copied().filter(move |glyph| {
+            glyph.count_ones() == pattern.count_ones()
+                && allowed.iter().enumerate().all(|(wire, segments)| {
+                    if pattern & 1 << wire != 0 {
+                        segments & glyph != 0
+                    } else {
+                        segments & !glyph != 0
+                    }
+                })
         })
-        .collect();
+    }
+
+    /// Narrows down the segments of each wire until nothing changes, returns `false` once
+    /// a wire has no segment left.
+    fn propagate(&self, patterns: &[Mask], allowed: &mut [Mask]) -> bool {
+        let mut changed = true;
 
-    let mut result: u32 = 0;
+        while changed {
+            changed = false;
+            for &pattern in patterns {
+                let (lit, unlit) = self
+                    .candidates(pattern, allowed)
+                    .fold((0, 0), |(lit, unlit), glyph| (lit | glyph, unlit | !glyph));
+                for (wire, segments) in allowed.iter_mut().enumerate() {
+                    let narrowed = *segments & if pattern & 1 << wire != 0 { lit } else { unlit };
+                    changed |= narrowed != *segments;
+                    *segments = narrowed;
+                }
+            }
+            // a segment taken by a decided wire is not available to the others
+            for wire in 0..allowed.len() {
+                let taken = allowed[wire];
+                if taken.count_ones() == 1 {
+                    for (other, segments) in allowed.iter_mut().enumerate() {
+                        if other != wire && *segments & taken != 0 {
+                            *segments &= !taken;
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            if allowed.contains(&0) {
+                return false;
+            }
+        }
 
-    for v in decoded_output {
-        result = 10 * result + *letters.get(&v).unwrap() as u32;
+        true
     }
 
-    result
-}
+    /// Collects up to two wirings consistent with the patterns, branching on the wire with
+    /// the fewest segments left.
+    fn search(&self, patterns: &[Mask], mut allowed: Vec<Mask>, found: &mut Vec<Wiring>) {
+        if found.len() > 1 || !self.propagate(patterns, &mut allowed) {
+            return;
+        }
 
-pub fn ex1() -> String {
-    let options: HashSet<usize> = HashSet::from([2, 4, 3, 7]);
-    let input_str = fs::read_to_string("inputs/day8.txt").expect("Could not read file");
-    let mut sum: u32 = 0;
-    for (_, output) in input_str.lines().map(|ln| process_line(ln)) {
-        for block in output.iter() {
-            if options.contains(&block.len()) {
-                sum += 1;
+        match (0..allowed.len())
+            .filter(|&wire| allowed[wire].count_ones() > 1)
+            .min_by_key(|&wire| allowed[wire].count_ones())
+        {
+            Some(wire) => {
+                for segment in bits(allowed[wire]) {
+                    let mut branch = allowed.clone();
+                    branch[wire] = 1 << segment;
+                    self.search(patterns, branch, found);
+                }
+            }
+            None => {
+                // every pattern has to be a glyph, not just have a candidate
+                let wiring: Wiring = allowed.iter().map(|&s| s.trailing_zeros() as u8).collect();
+                if patterns
+                    .iter()
+                    .all(|&pattern| self.glyphs.contains(&self.apply(&wiring, pattern)))
+                {
+                    found.push(wiring);
+                }
             }
         }
     }
 
-    sum.to_string()
+    /// Finds the only wiring under which each of the patterns of lit wires shows a glyph.
+    pub fn solve(&self, patterns: &[SegmentBlock]) -> Result<Wiring, DecodeError> {
+        if !patterns.iter().all(|pattern| self.connected(pattern)) {
+            return Err(DecodeError::NoSolution);
+        }
+        let patterns: Vec<Mask> = patterns.iter().map(mask).collect();
+        let mut found = Vec::new();
+        self.search(&patterns, vec![self.wires(); self.segments], &mut found);
+
+        match found.len() {
+            0 => Err(DecodeError::NoSolution),
+            1 => Ok(found.remove(0)),
+            _ => Err(DecodeError::Ambiguous(found.remove(0), found.remove(0))),
+        }
+    }
+
+    /// Mask of all of the wires, which are as many as the segments.
+    fn wires(&self) -> Mask {
+        if self.segments == Mask::BITS as usize {
+            !0
+        } else {
+            (1 << self.segments) - 1
+        }
+    }
+
+    fn apply(&self, wiring: &Wiring, pattern: Mask) -> Mask {
+        bits(pattern).fold(0, |mask, wire| mask | 1 << wiring[wire as usize])
+    }
+
+    /// Symbol shown by the pattern of lit wires under the wiring.
+    pub fn decode(&self, wiring: &Wiring, pattern: &SegmentBlock) -> Result<usize, DecodeError> {
+        let unknown = || DecodeError::UnknownGlyph(pattern.iter().copied().sorted().collect());
+        if !self.connected(pattern) {
+            return Err(unknown());
+        }
+        let glyph = self.apply(wiring, mask(pattern));
+
+        self.glyphs
+            .iter()
+            .position(|&g| g == glyph)
+            .ok_or_else(unknown)
+    }
 }
 
-pub fn ex2() -> String {
-    let input_str = fs::read_to_string("inputs/day8.txt").expect("Could not read file");
-    let mut solution: Solution;
-    let mut sum: u32 = 0;
-    for (code, output) in input_str.lines().map(|ln| process_line(ln)) {
+/// Number shown by the output digits of an entry.
+fn output_value(
+    table: &GlyphTable,
+    code: &LeftEncoded,
+    output: &RightEncoded,
+) -> Result<usize, DecodeError> {
+    let wiring = table.solve(code)?;
+
+    output.iter().try_fold(0, |value, block| {
+        Ok(10 * value + table.decode(&wiring, block)?)
+    })
+}
 
-        solution = solve(code);
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+    type Input = Vec<(LeftEncoded, RightEncoded)>;
 
-        sum += decode(output, solution);
+    fn parse(input: &str) -> Result<Vec<(LeftEncoded, RightEncoded)>, ParseError> {
+        parse_lines(input, process_line)
     }
 
-    sum.to_string()
+    fn part1(entries: &Vec<(LeftEncoded, RightEncoded)>) -> Answer {
+        let options: HashSet<usize> = HashSet::from([2, 4, 3, 7]);
+        let mut sum: u32 = 0;
+        for (_, output) in entries {
+            for block in output.iter() {
+                if options.contains(&block.len()) {
+                    sum += 1;
+                }
+            }
+        }
+
+        Ok(sum.to_string())
+    }
+
+    /// Sum of the output values, fails with the first entry that cannot be decoded.
+    fn part2(entries: &Vec<(LeftEncoded, RightEncoded)>) -> Answer {
+        let table = GlyphTable::seven_segment();
+        let mut sum: usize = 0;
+        for (k, (code, output)) in entries.iter().enumerate() {
+            match output_value(&table, code, output) {
+                Ok(value) => sum += value,
+                Err(err) => return Err(format!("line {}: {}", k + 1, err)),
+            }
+        }
+
+        Ok(sum.to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::{Day8, DecodeError, GlyphTable, SegmentBlock};
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "387");
-        assert_eq!(ex2(), "986034");
+        let input = Day8::parse(&fs::read_to_string("inputs/day8.txt").unwrap()).unwrap();
+        assert_eq!(Day8::part1(&input).unwrap(), "387");
+        assert_eq!(Day8::part2(&input).unwrap(), "986034");
+    }
+
+    #[test]
+    fn test_glyph_tables() {
+        let line =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+        let (code, output) = &Day8::parse(line).unwrap()[0];
+        let table = GlyphTable::seven_segment();
+        let wiring = table.solve(code).unwrap();
+        let digits: Vec<usize> = output
+            .iter()
+            .map(|b| table.decode(&wiring, b).unwrap())
+            .collect();
+        assert_eq!(digits, vec![5, 3, 5, 3]);
+        let entries = Day8::parse(&format!(
+            "{}\nab ab ab ab ab ab ab ab ab ab | ab ab ab ab",
+            line
+        ))
+        .unwrap();
+        assert_eq!(Day8::part2(&entries[..1].to_vec()).unwrap(), "5353");
+        assert!(Day8::part2(&entries).unwrap_err().starts_with("line 2: "));
+
+        // only the digits 1 and 7 leave most of the wiring open
+        assert!(matches!(
+            table.solve(&code[8..]),
+            Err(DecodeError::Ambiguous(_, _))
+        ));
+        let broken = [
+            SegmentBlock::from([0, 1, 2, 3, 4, 5, 6]),
+            SegmentBlock::from([0]),
+        ];
+        assert_eq!(table.solve(&broken), Err(DecodeError::NoSolution));
+
+        // a custom display with the symbols of a single line of three segments
+        let bars = GlyphTable::new(3, &[&[0], &[0, 1], &[0, 1, 2]]).unwrap();
+        let code = [
+            SegmentBlock::from([2]),
+            SegmentBlock::from([2, 0]),
+            SegmentBlock::from([0, 1, 2]),
+        ];
+        let wiring = bars.solve(&code).unwrap();
+        assert_eq!(wiring, vec![1, 2, 0]);
+        assert_eq!(bars.decode(&wiring, &SegmentBlock::from([0, 2])), Ok(1));
+        assert_eq!(
+            bars.decode(&wiring, &SegmentBlock::from([1])),
+            Err(DecodeError::UnknownGlyph(vec![1]))
+        );
+    }
+
+    #[test]
+    fn test_invalid_glyph_tables() {
+        assert_eq!(
+            GlyphTable::new(65, &[&[0]]).unwrap_err(),
+            DecodeError::TooManySegments(65)
+        );
+        assert_eq!(
+            GlyphTable::new(3, &[&[0], &[1, 3]]).unwrap_err(),
+            DecodeError::UnknownSegment {
+                symbol: 1,
+                segment: 3
+            }
+        );
+        assert!(GlyphTable::new(64, &[&[0, 63]]).is_ok());
+        assert!(GlyphTable::new(64, &[&[64]]).is_err());
+        assert!(GlyphTable::new(64, &[&[200]]).is_err());
+
+        let bars = GlyphTable::new(3, &[&[0], &[0, 1], &[0, 1, 2]]).unwrap();
+        assert_eq!(
+            bars.solve(&[SegmentBlock::from([200])]),
+            Err(DecodeError::NoSolution)
+        );
     }
 }
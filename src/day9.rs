@@ -1,9 +1,12 @@
+use crate::error::ParseError;
+use crate::solution::{Answer, Solution};
 use itertools::Itertools;
 use std::collections::VecDeque;
-use std::fs;
 
-type Row = Vec<u8>;
-type Board = Vec<Row>;
+pub struct Day9;
+
+pub type Row = Vec<u8>;
+pub type Board = Vec<Row>;
 
 #[derive(Debug)]
 struct Position {
@@ -24,7 +27,7 @@ impl BoardIterator {
             x: 0,
             y: 0,
             board_height: board.len(),
-            board_width: if board.len() == 0 { 0 } else { board[0].len() },
+            board_width: if board.is_empty() { 0 } else { board[0].len() },
         }
     }
 }
@@ -52,20 +55,39 @@ impl Iterator for BoardIterator {
     }
 }
 
-fn load_row(line: &str) -> Row {
-    line.chars()
-        .map(|c| c.to_digit(10).unwrap() as u8)
+fn load_row(line: &str) -> Result<Row, ParseError> {
+    line.char_indices()
+        .map(|(k, c)| match c.to_digit(10) {
+            Some(d) => Ok(d as u8),
+            None => Err(ParseError::at(
+                line,
+                &line[k..k + c.len_utf8()],
+                "height digit",
+            )),
+        })
         .collect()
 }
 
-fn load_board(s: String) -> Board {
+fn load_board(s: &str) -> Result<Board, ParseError> {
     let mut board: Board = Board::new();
 
-    for line in s.lines() {
-        board.push(load_row(line))
+    for (k, line) in s.lines().enumerate() {
+        let row = load_row(line).map_err(|e| e.offset_lines(k))?;
+        if board.first().is_some_and(|first| first.len() != row.len()) {
+            return Err(ParseError::at(
+                s,
+                line,
+                "row of the same length as the first one",
+            ));
+        }
+        board.push(row)
     }
 
-    board
+    if board.first().is_none_or(|first| first.is_empty()) {
+        return Err(ParseError::at_end(s, "row of heights"));
+    }
+
+    Ok(board)
 }
 
 fn is_low_point(board: &Board, pos: &Position) -> bool {
@@ -160,37 +182,43 @@ fn basin_size(board: &Board, pos: &Position) -> u32 {
     volume
 }
 
-pub fn ex1() -> String {
-    let input_str: String = fs::read_to_string("inputs/day9.txt").expect("Could not read file");
-    let board: Board = load_board(input_str);
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+    type Input = Board;
 
-    BoardIterator::new(&board)
-        .filter(|pos| is_low_point(&board, &pos))
-        .map(|pos| risk_level(&board, &pos))
-        .sum::<u16>()
-        .to_string()
-}
+    fn parse(input: &str) -> Result<Board, ParseError> {
+        load_board(input)
+    }
+
+    fn part1(board: &Board) -> Answer {
+        Ok(BoardIterator::new(board)
+            .filter(|pos| is_low_point(board, pos))
+            .map(|pos| risk_level(board, &pos))
+            .sum::<u16>()
+            .to_string())
+    }
 
-pub fn ex2() -> String {
-    let input_str: String = fs::read_to_string("inputs/day9.txt").expect("Could not read file");
-    let board: Board = load_board(input_str);
-
-    BoardIterator::new(&board)
-        .filter(|pos| is_low_point(&board, &pos))
-        .map(|pos| basin_size(&board, &pos))
-        .sorted_by(|a, b| a.cmp(&b).reverse())
-        .take(3)
-        .reduce(|acc, k| acc * k)
-        .unwrap()
-        .to_string()
+    fn part2(board: &Board) -> Answer {
+        Ok(BoardIterator::new(board)
+            .filter(|pos| is_low_point(board, pos))
+            .map(|pos| basin_size(board, &pos))
+            .sorted_by(|a, b| a.cmp(b).reverse())
+            .take(3)
+            .reduce(|acc, k| acc * k)
+            .unwrap()
+            .to_string())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ex1, ex2};
+    use super::Day9;
+    use crate::solution::Solution;
+    use std::fs;
     #[test]
     fn test_both_exercises() {
-        assert_eq!(ex1(), "468");
-        assert_eq!(ex2(), "1280496");
+        let input = Day9::parse(&fs::read_to_string("inputs/day9.txt").unwrap()).unwrap();
+        assert_eq!(Day9::part1(&input).unwrap(), "468");
+        assert_eq!(Day9::part2(&input).unwrap(), "1280496");
     }
 }
@@ -0,0 +1,119 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Error raised when a puzzle input does not conform to the expected format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Day of the puzzle whose input was being parsed.
+    pub day: u8,
+    /// Line of the input, starting from 1.
+    pub line: usize,
+    /// Column of the line in characters, starting from 1.
+    pub column: usize,
+    /// Description of what was expected at the given position.
+    pub expected: String,
+    /// Text found at the given position instead, empty at the end of a line or input.
+    pub found: String,
+}
+
+impl ParseError {
+    /// Creates an error pointing at `token`, which has to be a slice of `text`. The position
+    /// is relative to the beginning of `text`.
+    pub fn at(text: &str, token: &str, expected: &str) -> ParseError {
+        let offset = (token.as_ptr() as usize)
+            .saturating_sub(text.as_ptr() as usize)
+            .min(text.len());
+        let line_start = text[..offset].rfind('\n').map_or(0, |k| k + 1);
+
+        ParseError {
+            day: 0,
+            line: text[..offset].matches('\n').count() + 1,
+            column: text[line_start..offset].chars().count() + 1,
+            expected: String::from(expected),
+            found: String::from(token),
+        }
+    }
+
+    /// Creates an error pointing right after the end of `text`, used when it ends prematurely.
+    pub fn at_end(text: &str, expected: &str) -> ParseError {
+        ParseError::at(text, &text[text.len()..], expected)
+    }
+
+    /// Moves the error by `lines` lines, used when the position was computed relative to
+    /// a single line of a larger input.
+    pub fn offset_lines(mut self, lines: usize) -> ParseError {
+        self.line += lines;
+        self
+    }
+
+    pub fn in_day(mut self, day: u8) -> ParseError {
+        self.day = day;
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // errors outside of puzzle inputs, such as in the answers file, have no day
+        if self.day > 0 {
+            write!(f, "day {}, ", self.day)?;
+        }
+        write!(
+            f,
+            "line {}, column {}: expected {}, found ",
+            self.line, self.column, self.expected
+        )?;
+
+        if self.found.is_empty() {
+            write!(f, "nothing")
+        } else {
+            write!(f, "'{}'", self.found)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses `token`, a slice of `text`, reporting its position on failure.
+pub fn parse_token<T: FromStr>(text: &str, token: &str, expected: &str) -> Result<T, ParseError> {
+    token
+        .parse::<T>()
+        .map_err(|_| ParseError::at(text, token, expected))
+}
+
+/// Parses every line of `input` with `f`. Positions of the errors returned by `f` are
+/// expected to be relative to the line and are moved to the position of the line in `input`.
+pub fn parse_lines<T, F>(input: &str, f: F) -> Result<Vec<T>, ParseError>
+where
+    F: Fn(&str) -> Result<T, ParseError>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(k, ln)| f(ln).map_err(|e| e.offset_lines(k)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_lines, parse_token, ParseError};
+    #[test]
+    fn test_error_positions() {
+        let input = "1,2\n3,x4\n";
+        let err = parse_lines(input, |ln| {
+            ln.split(',')
+                .map(|s| parse_token::<u8>(ln, s, "number"))
+                .collect::<Result<Vec<u8>, ParseError>>()
+        })
+        .unwrap_err();
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 3, "x4"));
+
+        let err = ParseError::at_end(input, "fold instruction").in_day(13);
+        assert_eq!((err.day, err.line, err.column), (13, 3, 1));
+        assert_eq!(
+            err.to_string(),
+            "day 13, line 3, column 1: expected fold instruction, found nothing"
+        );
+    }
+}
@@ -1,53 +1,730 @@
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day2;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+use aoc_2021::answers::{parse_answers, AnswerSet};
+use aoc_2021::report::{self, Format, Record};
+use aoc_2021::runner::run_in_order;
+use aoc_2021::{bench, day1, DAYS};
+use cpu_time::ThreadTime;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::process;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "\
+usage: aoc_2021 [run] [DAYS] [--part 1|2] [--jobs N] [--format FORMAT] [--input PATH]
+       aoc_2021 bench [DAYS] [--iterations N] [--input PATH] [--json PATH]
+       aoc_2021 verify [DAYS] [--answers PATH] [--set NAME]
+
+DAYS is a comma separated list of `all`, a single day (`14`) or a range
+of days (`1..10`, `1..=10`). Defaults to `all`.
+
+PATH is either a directory with `dayN.txt` files, a single input file or
+`-` for the standard input. The last two require a single day. Defaults
+to the `inputs` directory. Day 1 is solved as the standard input is read,
+so it works on inputs that do not fit in memory.
+
+`run` solves the days on N threads (1 by default) with `--jobs`, printing
+the answers in order of the days. The total elapsed time and the CPU time
+summed over the days are printed to the standard error at the end.
+
+FORMAT of the answers is `text` (default), `json` or `csv`. The last two
+give a record with the day, part, answer, time spent on solving the part
+in nanoseconds and error for each of the solved parts.
+
+`bench` times parsing and both parts of each day N times (5 by default)
+and prints the minimum, median and maximum. With `--json`, the timings
+are also written to the given file, `-` for the standard output, in
+which case the table goes to the standard error.
+
+`verify` checks the answers of each day against the expected ones from
+the answers file (`answers.toml` by default) and reports whether they
+pass, fail or are unknown. Each set of inputs in the file is checked,
+unless a single one is chosen with `--set`.";
+
+const DEFAULT_ITERATIONS: usize = 5;
+const DEFAULT_JOBS: usize = 1;
+const DEFAULT_ANSWERS: &str = "answers.toml";
+const VERIFY_HEADER: &str = "Set         Day  Part  Status   Answer";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Run {
+        days: Vec<u8>,
+        part: Option<u8>,
+        jobs: usize,
+        format: Format,
+        input: Option<String>,
+    },
+    Bench {
+        days: Vec<u8>,
+        iterations: usize,
+        input: Option<String>,
+        json: Option<String>,
+    },
+    Verify {
+        days: Vec<u8>,
+        answers: String,
+        set: Option<String>,
+    },
+    Help,
+}
+
+fn parse_day(s: &str) -> Result<u8, String> {
+    match s.parse::<u8>() {
+        Ok(day) if (1..=DAYS.len() as u8).contains(&day) => Ok(day),
+        _ => Err(format!("'{}' is not a valid day", s)),
+    }
+}
+
+/// Checks that the range of days `item` is not empty, which it is when reversed.
+fn non_empty<R: ExactSizeIterator<Item = u8>>(item: &str, range: R) -> Result<R, String> {
+    if range.len() == 0 {
+        Err(format!("'{}' contains no days", item))
+    } else {
+        Ok(range)
+    }
+}
+
+fn parse_days(spec: &str) -> Result<Vec<u8>, String> {
+    let mut days: Vec<u8> = Vec::new();
+
+    for item in spec.split(',') {
+        if item == "all" {
+            days.extend(1..=DAYS.len() as u8);
+        } else if let Some((from, to)) = item.split_once("..=") {
+            days.extend(non_empty(item, parse_day(from)?..=parse_day(to)?)?);
+        } else if let Some((from, to)) = item.split_once("..") {
+            days.extend(non_empty(item, parse_day(from)?..parse_day(to)?)?);
+        } else {
+            days.push(parse_day(item)?);
+        }
+    }
+
+    // run each day only once and always in order
+    days.sort_unstable();
+    days.dedup();
+    Ok(days)
+}
+
+fn parse_part(s: Option<String>) -> Result<u8, String> {
+    match s.as_deref() {
+        Some("1") => Ok(1),
+        Some("2") => Ok(2),
+        Some(s) => Err(format!("'{}' is not a valid part", s)),
+        None => Err(String::from("missing value for --part")),
+    }
+}
+
+/// Parses the positive value of the option `--name`.
+fn parse_count(s: Option<String>, name: &str) -> Result<usize, String> {
+    match s.as_deref().map(str::parse::<usize>) {
+        Some(Ok(n)) if n > 0 => Ok(n),
+        Some(_) => Err(format!(
+            "'{}' is not a valid number of {}",
+            s.unwrap(),
+            name
+        )),
+        None => Err(format!("missing value for --{}", name)),
+    }
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.peekable();
+    let command = args
+        .next_if(|arg| ["run", "bench", "verify"].contains(&arg.as_str()))
+        .unwrap_or_else(|| String::from("run"));
+    let (is_bench, is_verify) = (command == "bench", command == "verify");
+
+    let mut days: Option<Vec<u8>> = None;
+    let mut part: Option<u8> = None;
+    let mut input: Option<String> = None;
+    let mut iterations = DEFAULT_ITERATIONS;
+    let mut jobs = DEFAULT_JOBS;
+    let mut format = Format::Text;
+    let mut json: Option<String> = None;
+    let mut answers = String::from(DEFAULT_ANSWERS);
+    let mut set: Option<String> = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" | "help" => return Ok(Command::Help),
+            "-p" | "--part" if command == "run" => part = Some(parse_part(args.next())?),
+            "-n" | "--iterations" if is_bench => {
+                iterations = parse_count(args.next(), "iterations")?;
+            }
+            "-j" | "--jobs" if command == "run" => jobs = parse_count(args.next(), "jobs")?,
+            "-f" | "--format" if command == "run" => {
+                format = args.next().ok_or("missing value for --format")?.parse()?;
+            }
+            "--json" if is_bench => json = Some(args.next().ok_or("missing value for --json")?),
+            "--answers" if is_verify => {
+                answers = args.next().ok_or("missing value for --answers")?;
+            }
+            "--set" if is_verify => set = Some(args.next().ok_or("missing value for --set")?),
+            "-i" | "--input" if !is_verify => {
+                input = Some(args.next().ok_or("missing value for --input")?);
+            }
+            s if s.starts_with('-') => return Err(format!("unknown option '{}'", s)),
+            s if days.is_none() => days = Some(parse_days(s)?),
+            s => return Err(format!("unexpected argument '{}'", s)),
+        }
+    }
+
+    let days = days.unwrap_or_else(|| (1..=DAYS.len() as u8).collect());
+    if let Some(path) = &input {
+        if days.len() != 1 && !Path::new(path).is_dir() {
+            return Err(format!(
+                "input '{}' can only be used with a single day",
+                path
+            ));
+        }
+    }
+
+    if is_bench {
+        Ok(Command::Bench {
+            days,
+            iterations,
+            input,
+            json,
+        })
+    } else if is_verify {
+        Ok(Command::Verify { days, answers, set })
+    } else {
+        Ok(Command::Run {
+            days,
+            part,
+            jobs,
+            format,
+            input,
+        })
+    }
+}
+
+/// Reads the puzzle input of the given day, see `USAGE` for the meaning of `path`.
+fn read_input(day: u8, path: Option<&str>) -> io::Result<String> {
+    match path {
+        Some("-") => {
+            let mut s = String::new();
+            io::stdin().read_to_string(&mut s)?;
+            Ok(s)
+        }
+        Some(path) if !Path::new(path).is_dir() => fs::read_to_string(path),
+        dir => {
+            fs::read_to_string(Path::new(dir.unwrap_or("inputs")).join(format!("day{}.txt", day)))
+        }
+    }
+}
+
+/// Outcome of running a single day.
+struct DayRun {
+    day: u8,
+    /// Records of the requested parts, all of them fail if the input could not be parsed.
+    records: Vec<Record>,
+    cpu_time: Duration,
+}
+
+/// Calls `f`, turning a panic into an error with the panic message, so that a single
+/// failing day does not bring down the others.
+fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        match (
+            payload.downcast_ref::<&str>(),
+            payload.downcast_ref::<String>(),
+        ) {
+            (Some(msg), _) => String::from(*msg),
+            (_, Some(msg)) => msg.clone(),
+            _ => String::from("unknown panic"),
+        }
+    })
+}
+
+/// Solves the given parts of day 1 as the standard input is read, without keeping it in
+/// memory. Both parts are solved in the same pass, so they share the elapsed time.
+fn stream_day1(parts: Vec<u8>) -> DayRun {
+    let cpu_start = ThreadTime::now();
+    let start = Instant::now();
+
+    let windows: Vec<usize> = parts
+        .iter()
+        .map(|part| day1::WINDOWS[*part as usize - 1])
+        .collect();
+    let counts =
+        day1::general_solutions_from(io::stdin().lock(), &windows).map_err(|err| {
+            match err.kind() {
+                io::ErrorKind::InvalidData => err.to_string(),
+                _ => format!("day 1: could not read input: {}", err),
+            }
+        });
+    let elapsed = start.elapsed();
+    let records = parts
+        .into_iter()
+        .enumerate()
+        .map(|(k, part)| Record {
+            day: 1,
+            part,
+            answer: counts
+                .as_ref()
+                .map(|counts| counts[k].to_string())
+                .map_err(String::clone),
+            elapsed,
+        })
+        .collect();
+
+    DayRun {
+        day: 1,
+        records,
+        cpu_time: cpu_start.elapsed(),
+    }
+}
+
+/// Solves the given part of the day, or both of them if none is given.
+fn run_day(day: u8, part: Option<u8>, path: Option<&str>) -> DayRun {
+    if day == 1 && path == Some("-") {
+        return stream_day1(part.map_or(vec![1, 2], |part| vec![part]));
+    }
+
+    let solver = DAYS[day as usize - 1];
+    let cpu_start = ThreadTime::now();
+
+    let input = read_input(day, path)
+        .map_err(|err| format!("day {}: could not read input: {}", day, err))
+        .and_then(|s| {
+            catch_panic(|| solver.parse_input(&s))
+                .map_err(|msg| format!("day {}: parser panicked: {}", day, msg))?
+                .map_err(|err| err.to_string())
+        });
+    let records = part
+        .map_or(vec![1, 2], |part| vec![part])
+        .into_iter()
+        .map(|part| {
+            let start = Instant::now();
+            let answer = match &input {
+                Ok(input) => match catch_panic(|| solver.solve_part(&**input, part)) {
+                    Ok(answer) => {
+                        answer.map_err(|msg| format!("day {}, part {}: {}", day, part, msg))
+                    }
+                    Err(msg) => Err(format!("day {}, part {}: panicked: {}", day, part, msg)),
+                },
+                Err(msg) => Err(msg.clone()),
+            };
+
+            Record {
+                day,
+                part,
+                answer,
+                elapsed: start.elapsed(),
+            }
+        })
+        .collect();
+
+    DayRun {
+        day,
+        records,
+        cpu_time: cpu_start.elapsed(),
+    }
+}
+
+/// Prints the answers of the day on a single line, with `failed` in place of the parts that
+/// failed, and their errors to the standard error. Only the error is printed if no part has
+/// an answer, such as when the input could not be parsed.
+fn print_text(day_run: DayRun) {
+    let mut errors: Vec<String> = Vec::new();
+    let mut answered = false;
+    let answers: Vec<String> = day_run
+        .records
+        .into_iter()
+        .map(|r| match r.answer {
+            Ok(answer) => {
+                answered = true;
+                answer
+            }
+            Err(msg) => {
+                // parts of an unparsable input share the same error
+                if !errors.contains(&msg) {
+                    errors.push(msg);
+                }
+                String::from("failed")
+            }
+        })
+        .collect();
+
+    if answered {
+        println!("Day {:>2}: {}", day_run.day, answers.join(", "));
+    }
+    for msg in errors {
+        eprintln!("error: {}", msg);
+    }
+}
+
+/// Runs the given days on `jobs` threads, reporting the ones that fail and continuing with
+/// the next day. Returns whether all of the days succeeded.
+fn run(days: &[u8], part: Option<u8>, jobs: usize, format: Format, path: Option<&str>) -> bool {
+    let mut success = true;
+    let mut cpu_time = Duration::ZERO;
+    let mut first_record = true;
+    let start = Instant::now();
+
+    match format {
+        Format::Text => {}
+        Format::Json => println!("["),
+        Format::Csv => println!("{}", report::CSV_HEADER),
+    }
+
+    run_in_order(
+        days.len(),
+        jobs,
+        |k| run_day(days[k], part, path),
+        |day_run| {
+            success &= day_run.records.iter().all(|r| r.answer.is_ok());
+            cpu_time += day_run.cpu_time;
+
+            match format {
+                Format::Text => print_text(day_run),
+                Format::Json => {
+                    for record in &day_run.records {
+                        // the separator is only known to be needed once the next record comes
+                        let separator = if first_record { "" } else { ",\n" };
+                        print!("{}  {}", separator, record.json());
+                        first_record = false;
+                    }
+                }
+                Format::Csv => {
+                    for record in &day_run.records {
+                        println!("{}", record.csv());
+                    }
+                }
+            }
+        },
+    );
+
+    if format == Format::Json {
+        if !first_record {
+            println!();
+        }
+        println!("]");
+    }
+
+    eprintln!("Elapsed {:.1?}, CPU time {:.1?}", start.elapsed(), cpu_time);
+    success
+}
+
+/// Times the given days, see `USAGE`. Returns whether all of the days succeeded.
+fn bench(days: &[u8], iterations: usize, path: Option<&str>, json: Option<&str>) -> bool {
+    let mut success = true;
+    let mut timings = Vec::new();
+    // The table moves to the standard error when the JSON report takes the standard output.
+    let print_row = |row: &dyn fmt::Display| {
+        if json == Some("-") {
+            eprintln!("{}", row);
+        } else {
+            println!("{}", row);
+        }
+    };
+
+    print_row(&bench::TABLE_HEADER);
+    for solver in days.iter().map(|day| DAYS[*day as usize - 1]) {
+        let day = solver.day();
+        let input_str = match read_input(day, path) {
+            Ok(s) => s,
+            Err(err) => {
+                eprintln!("Day {:>2}: could not read input: {}", day, err);
+                success = false;
+                continue;
+            }
+        };
+
+        match catch_panic(|| bench::bench_day(solver, &input_str, iterations)) {
+            Ok(Ok(day_timings)) => {
+                print_row(&day_timings);
+                timings.push(day_timings);
+            }
+            Ok(Err(err)) => {
+                eprintln!("error: {}", err);
+                success = false;
+            }
+            Err(msg) => {
+                eprintln!("error: day {}: panicked: {}", day, msg);
+                success = false;
+            }
+        }
+    }
+
+    let report = bench::json(iterations, &timings);
+    match json {
+        Some("-") => println!("{}", report),
+        Some(path) => {
+            if let Err(err) = fs::write(path, report + "\n") {
+                eprintln!("could not write '{}': {}", path, err);
+                success = false;
+            }
+        }
+        None => {}
+    }
+
+    success
+}
+
+/// Prints a row of the verification table, answers are escaped to fit on a single line.
+fn print_verify_row(set: &str, day: u8, part: u8, status: &str, answer: &str) {
+    let row = format!(
+        "{:<10} {:>4} {:>5}  {:<7}  {}",
+        set,
+        day,
+        part,
+        status,
+        answer.escape_debug()
+    );
+    println!("{}", row.trim_end());
+}
+
+/// Checks the answers of the given days in every chosen set of the answers file, see
+/// `USAGE`. Parts with an unknown answer are not run. Returns whether none of them failed.
+fn verify(days: &[u8], path: &str, set: Option<&str>) -> bool {
+    let sets = match fs::read_to_string(path) {
+        Ok(s) => match parse_answers(&s) {
+            Ok(sets) => sets,
+            Err(err) => {
+                eprintln!("error: {}: {}", path, err);
+                return false;
+            }
+        },
+        Err(err) => {
+            eprintln!("could not read '{}': {}", path, err);
+            return false;
+        }
+    };
+    let sets: Vec<&AnswerSet> = sets
+        .iter()
+        .filter(|s| set.is_none_or(|name| s.name == name))
+        .collect();
+    if sets.is_empty() {
+        eprintln!("error: no set '{}' in '{}'", set.unwrap_or_default(), path);
+        return false;
+    }
+
+    let mut success = true;
+    let (mut passed, mut failed, mut unknown) = (0, 0, 0);
+
+    println!("{}", VERIFY_HEADER);
+    for set in sets {
+        for solver in days.iter().map(|day| DAYS[*day as usize - 1]) {
+            let day = solver.day();
+            let expected = [1, 2].map(|part| set.expected(day, part));
+            if expected.iter().all(Option::is_none) {
+                for part in [1, 2] {
+                    print_verify_row(&set.name, day, part, "unknown", "");
+                }
+                unknown += 2;
+                continue;
+            }
+
+            let input = match read_input(day, Some(&set.dir)) {
+                Ok(s) => match catch_panic(|| solver.parse_input(&s)) {
+                    Ok(input) => input.map_err(|err| err.to_string()),
+                    Err(msg) => {
+                        // Without a parsed input, every part with an expected answer fails.
+                        for (part, expected) in (1..=2).zip(expected) {
+                            if let Some(expected) = expected {
+                                let found =
+                                    format!("parser panicked: {} (expected {})", msg, expected);
+                                print_verify_row(&set.name, day, part, "fail", &found);
+                                failed += 1;
+                            } else {
+                                print_verify_row(&set.name, day, part, "unknown", "");
+                                unknown += 1;
+                            }
+                        }
+                        success = false;
+                        continue;
+                    }
+                },
+                Err(err) => Err(format!("could not read input: {}", err)),
+            };
+            let input = match input {
+                Ok(input) => input,
+                Err(msg) => {
+                    eprintln!("error: {}: {}", set.name, msg);
+                    success = false;
+                    continue;
+                }
+            };
+
+            for (part, expected) in (1..=2).zip(expected) {
+                let Some(expected) = expected else {
+                    print_verify_row(&set.name, day, part, "unknown", "");
+                    unknown += 1;
+                    continue;
+                };
+
+                match catch_panic(|| solver.solve_part(&*input, part)) {
+                    Ok(Ok(answer)) if answer == expected => {
+                        print_verify_row(&set.name, day, part, "pass", &answer);
+                        passed += 1;
+                    }
+                    answer => {
+                        let found = match answer {
+                            Ok(Ok(answer)) => format!("{} (expected {})", answer, expected),
+                            Ok(Err(msg)) => format!("error: {} (expected {})", msg, expected),
+                            Err(msg) => format!("panicked: {} (expected {})", msg, expected),
+                        };
+                        print_verify_row(&set.name, day, part, "fail", &found);
+                        failed += 1;
+                        success = false;
+                    }
+                }
+            }
+        }
+    }
+
+    println!(
+        "\n{} passed, {} failed, {} unknown",
+        passed, failed, unknown
+    );
+    success
+}
 
 fn main() {
-    println!("Day  1: {}, {}", day1::ex1(), day1::ex2());
-    println!("Day  2: {}, {}", day2::ex1(), day2::ex2());
-    println!("Day  3: {}, {}", day3::ex1(), day3::ex2());
-    println!("Day  4: {}, {}", day4::ex1(), day4::ex2());
-    println!("Day  5: {}, {}", day5::ex1(), day5::ex2());
-    println!("Day  6: {}, {}", day6::ex1(), day6::ex2());
-    println!("Day  7: {}, {}", day7::ex1(), day7::ex2());
-    println!("Day  8: {}, {}", day8::ex1(), day8::ex2());
-    println!("Day  9: {}, {}", day9::ex1(), day9::ex2());
-    println!("Day 10: {}, {}", day10::ex1(), day10::ex2());
-    println!("Day 11: {}, {}", day11::ex1(), day11::ex2());
-    println!("Day 12: {}, {}", day12::ex1(), day12::ex2());
-    println!("Day 13: {}, {}", day13::ex1(), day13::ex2());
-    println!("Day 14: {}, {}", day14::ex1(), day14::ex2());
-    println!("Day 15: {}, {}", day15::ex1(), day15::ex2());
-    println!("Day 16: {}, {}", day16::ex1(), day16::ex2());
-    println!("Day 17: {}, {}", day17::ex1(), day17::ex2());
-    println!("Day 18: {}, {}", day18::ex1(), day18::ex2());
-    println!("Day 19: {}, {}", day19::ex1(), day19::ex2());
-    println!("Day 20: {}, {}", day20::ex1(), day20::ex2());
-    println!("Day 21: {}, {}", day21::ex1(), day21::ex2());
-    println!("Day 22: {}, {}", day22::ex1(), day22::ex2());
-    println!("Day 23: {}, {}", day23::ex1(), day23::ex2());
-    println!("Day 24: {}, {}", day24::ex1(), day24::ex2());
-    println!("Day 25: {}, {}", day25::ex1(), day25::ex2());
+    match parse_args(env::args().skip(1)) {
+        Ok(Command::Run {
+            days,
+            part,
+            jobs,
+            format,
+            input,
+        }) => {
+            if !run(&days, part, jobs, format, input.as_deref()) {
+                process::exit(1);
+            }
+        }
+        Ok(Command::Bench {
+            days,
+            iterations,
+            input,
+            json,
+        }) => {
+            if !bench(&days, iterations, input.as_deref(), json.as_deref()) {
+                process::exit(1);
+            }
+        }
+        Ok(Command::Verify { days, answers, set }) => {
+            if !verify(&days, &answers, set.as_deref()) {
+                process::exit(1);
+            }
+        }
+        Ok(Command::Help) => println!("{}", USAGE),
+        Err(msg) => {
+            eprintln!("error: {}\n\n{}", msg, USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{catch_panic, parse_args, parse_days, Command, Format};
+
+    fn args(s: &str) -> impl Iterator<Item = String> + '_ {
+        s.split_whitespace().map(String::from)
+    }
+
+    #[test]
+    fn test_argument_parsing() {
+        assert_eq!(parse_days("all").unwrap().len(), 25);
+        assert_eq!(parse_days("3,1..3").unwrap(), vec![1, 2, 3]);
+        assert_eq!(parse_days("23..=25").unwrap(), vec![23, 24, 25]);
+        assert!(parse_days("0").is_err());
+        assert!(parse_days("1..=26").is_err());
+        assert!(parse_days("10..3").is_err());
+        assert!(parse_days("3..3").is_err());
+        assert!(parse_days("5..=4").is_err());
+        assert_eq!(parse_days("3..=3").unwrap(), vec![3]);
+
+        assert_eq!(
+            parse_args(args("run 14 --part 2")),
+            Ok(Command::Run {
+                days: vec![14],
+                part: Some(2),
+                jobs: 1,
+                format: Format::Text,
+                input: None
+            })
+        );
+        assert_eq!(
+            parse_args(args("6 --input -")),
+            Ok(Command::Run {
+                days: vec![6],
+                part: None,
+                jobs: 1,
+                format: Format::Text,
+                input: Some(String::from("-"))
+            })
+        );
+        assert_eq!(
+            parse_args(args("1..=5 -j 4")),
+            Ok(Command::Run {
+                days: vec![1, 2, 3, 4, 5],
+                part: None,
+                jobs: 4,
+                format: Format::Text,
+                input: None
+            })
+        );
+        assert!(parse_args(args("--jobs 0")).is_err());
+        assert!(parse_args(args("bench --jobs 2")).is_err());
+        assert_eq!(
+            parse_args(args("8 --format csv")),
+            Ok(Command::Run {
+                days: vec![8],
+                part: None,
+                jobs: 1,
+                format: Format::Csv,
+                input: None
+            })
+        );
+        assert!(parse_args(args("--format xml")).is_err());
+        assert!(parse_args(args("1..=3 --input -")).is_err());
+        assert_eq!(parse_args(args("")), parse_args(args("run all")),);
+        assert!(parse_args(args("run 1 2")).is_err());
+        assert!(parse_args(args("--part 3")).is_err());
+
+        assert_eq!(
+            parse_args(args("bench 19,23 -n 3 --json -")),
+            Ok(Command::Bench {
+                days: vec![19, 23],
+                iterations: 3,
+                input: None,
+                json: Some(String::from("-"))
+            })
+        );
+        assert!(parse_args(args("bench --part 1")).is_err());
+        assert!(parse_args(args("bench --iterations 0")).is_err());
+        assert!(parse_args(args("run --json out.json")).is_err());
+
+        assert_eq!(
+            parse_args(args("verify 1..=3 --set examples")),
+            Ok(Command::Verify {
+                days: vec![1, 2, 3],
+                answers: String::from("answers.toml"),
+                set: Some(String::from("examples"))
+            })
+        );
+        assert!(parse_args(args("verify --input inputs")).is_err());
+        assert!(parse_args(args("run --answers answers.toml")).is_err());
+    }
+
+    #[test]
+    fn test_catch_panic() {
+        assert_eq!(catch_panic(|| 1), Ok(1));
+        assert_eq!(
+            catch_panic(|| -> u8 { panic!("boom") }),
+            Err(String::from("boom"))
+        );
+        let day = 3;
+        assert_eq!(
+            catch_panic(|| -> u8 { panic!("day {}", day) }),
+            Err(String::from("day 3"))
+        );
+    }
 }
@@ -0,0 +1,139 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const CSV_HEADER: &str = "day,part,answer,elapsed_ns,error";
+
+/// Output format of the solved parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("'{}' is not a valid format", s)),
+        }
+    }
+}
+
+/// Outcome of solving a single part of a day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    /// Answer to the part, or the message describing why it could not be solved.
+    pub answer: Result<String, String>,
+    /// Time spent on solving the part, not including reading and parsing of the input.
+    pub elapsed: Duration,
+}
+
+impl Record {
+    /// Line of the CSV output, see `CSV_HEADER` for the columns.
+    pub fn csv(&self) -> String {
+        let (answer, error) = match &self.answer {
+            Ok(answer) => (csv_field(answer), String::new()),
+            Err(msg) => (String::new(), csv_field(msg)),
+        };
+
+        format!(
+            "{},{},{},{},{}",
+            self.day,
+            self.part,
+            answer,
+            self.elapsed.as_nanos(),
+            error
+        )
+    }
+
+    /// JSON object with the record, the missing one of `answer` and `error` is `null`.
+    pub fn json(&self) -> String {
+        let (answer, error) = match &self.answer {
+            Ok(answer) => (json_string(answer), String::from("null")),
+            Err(msg) => (String::from("null"), json_string(msg)),
+        };
+
+        format!(
+            "{{\"day\": {}, \"part\": {}, \"answer\": {}, \"elapsed_ns\": {}, \"error\": {}}}",
+            self.day,
+            self.part,
+            answer,
+            self.elapsed.as_nanos(),
+            error
+        )
+    }
+}
+
+/// Quotes the field if it contains a separator, a quote or a line break.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        String::from(s)
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Format, Record};
+    use std::time::Duration;
+    #[test]
+    fn test_record_formats() {
+        assert_eq!("csv".parse::<Format>(), Ok(Format::Csv));
+        assert!("xml".parse::<Format>().is_err());
+
+        let record = Record {
+            day: 13,
+            part: 2,
+            answer: Ok(String::from("#.\n\"#")),
+            elapsed: Duration::from_micros(3),
+        };
+        assert_eq!(record.csv(), "13,2,\"#.\n\"\"#\",3000,");
+        assert_eq!(
+            record.json(),
+            "{\"day\": 13, \"part\": 2, \"answer\": \"#.\\n\\\"#\", \"elapsed_ns\": 3000, \"error\": null}"
+        );
+
+        let record = Record {
+            day: 4,
+            part: 1,
+            answer: Err(String::from(
+                "day 4, line 1, column 3: expected number, found 'x'",
+            )),
+            elapsed: Duration::ZERO,
+        };
+        assert_eq!(
+            record.csv(),
+            "4,1,,0,\"day 4, line 1, column 3: expected number, found 'x'\""
+        );
+        assert_eq!(
+            record.json(),
+            "{\"day\": 4, \"part\": 1, \"answer\": null, \"elapsed_ns\": 0, \
+             \"error\": \"day 4, line 1, column 3: expected number, found 'x'\"}"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Computes `f(0), ..., f(count - 1)` on `jobs` threads and passes the results to `report`
+/// in order of their indices, each as soon as it and all of the preceding ones are done.
+pub fn run_in_order<T, F, R>(count: usize, jobs: usize, f: F, mut report: R)
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+    R: FnMut(T),
+{
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, count.max(1)) {
+            let sender = sender.clone();
+            let (next, f) = (&next, &f);
+            scope.spawn(move || loop {
+                let k = next.fetch_add(1, Ordering::Relaxed);
+                if k >= count || sender.send((k, f(k))).is_err() {
+                    break;
+                }
+            });
+        }
+        // the workers hold the remaining senders, the loop below ends once all of them finish
+        drop(sender);
+
+        let mut pending = HashMap::new();
+        let mut reported = 0;
+        for (k, result) in receiver {
+            pending.insert(k, result);
+            while let Some(result) = pending.remove(&reported) {
+                report(result);
+                reported += 1;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_in_order;
+    use std::thread;
+    use std::time::Duration;
+    #[test]
+    fn test_run_in_order() {
+        for jobs in [1, 3, 20] {
+            let mut results = Vec::new();
+            run_in_order(
+                10,
+                jobs,
+                |k| {
+                    // finish the later tasks first
+                    thread::sleep(Duration::from_millis(10 - k as u64));
+                    k * k
+                },
+                |r| results.push(r),
+            );
+            assert_eq!(results, (0..10).map(|k| k * k).collect::<Vec<usize>>());
+        }
+    }
+}
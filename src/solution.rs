@@ -0,0 +1,126 @@
+use std::any::Any;
+
+use crate::error::ParseError;
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21,
+    day22, day23, day24, day25, day3, day4, day5, day6, day7, day8, day9,
+};
+
+/// Answer to a part of a puzzle, or the message explaining why the parsed input has none.
+pub type Answer = Result<String, String>;
+
+/// Common interface of all the daily puzzles. The input is parsed only once
+/// and both parts are then computed from the same parsed representation.
+pub trait Solution {
+    /// Day of the advent calendar the puzzle belongs to.
+    const DAY: u8;
+
+    /// Parsed representation of the puzzle input.
+    type Input: 'static;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Answer;
+    fn part2(input: &Self::Input) -> Answer;
+
+    /// Solves part 1 directly from the text of the puzzle input.
+    fn solve_part1(input: &str) -> Result<Answer, ParseError> {
+        let input = Self::parse(input).map_err(|e| e.in_day(Self::DAY))?;
+        Ok(Self::part1(&input))
+    }
+
+    /// Solves part 2 directly from the text of the puzzle input.
+    fn solve_part2(input: &str) -> Result<Answer, ParseError> {
+        let input = Self::parse(input).map_err(|e| e.in_day(Self::DAY))?;
+        Ok(Self::part2(&input))
+    }
+}
+
+/// Type-erased counterpart of [`Solution`], so that solutions with different
+/// input types can be stored in a single table and handled uniformly.
+pub trait Solver: Sync {
+    fn day(&self) -> u8;
+    fn parse_input(&self, input: &str) -> Result<Box<dyn Any>, ParseError>;
+    fn solve_part(&self, input: &dyn Any, part: u8) -> Answer;
+    fn solve(&self, input: &str, part: u8) -> Result<Answer, ParseError>;
+}
+
+impl<S: Solution + Sync> Solver for S {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn parse_input(&self, input: &str) -> Result<Box<dyn Any>, ParseError> {
+        match S::parse(input) {
+            Ok(input) => Ok(Box::new(input)),
+            Err(e) => Err(e.in_day(S::DAY)),
+        }
+    }
+
+    fn solve_part(&self, input: &dyn Any, part: u8) -> Answer {
+        let input = input
+            .downcast_ref::<S::Input>()
+            .expect("Input was not parsed by the same solution");
+
+        match part {
+            1 => S::part1(input),
+            2 => S::part2(input),
+            _ => panic!("Unknown part {}", part),
+        }
+    }
+
+    fn solve(&self, input: &str, part: u8) -> Result<Answer, ParseError> {
+        match part {
+            1 => S::solve_part1(input),
+            2 => S::solve_part2(input),
+            _ => panic!("Unknown part {}", part),
+        }
+    }
+}
+
+/// All registered solutions, indexed by `day - 1`.
+pub static DAYS: [&dyn Solver; 25] = [
+    &day1::Day1,
+    &day2::Day2,
+    &day3::Day3,
+    &day4::Day4,
+    &day5::Day5,
+    &day6::Day6,
+    &day7::Day7,
+    &day8::Day8,
+    &day9::Day9,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+    &day18::Day18,
+    &day19::Day19,
+    &day20::Day20,
+    &day21::Day21,
+    &day22::Day22,
+    &day23::Day23,
+    &day24::Day24,
+    &day25::Day25,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::DAYS;
+    #[test]
+    fn test_registry_order() {
+        for (k, solver) in DAYS.iter().enumerate() {
+            assert_eq!(solver.day() as usize, k + 1);
+        }
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        for solver in DAYS {
+            let err = solver.parse_input("garbage\n").unwrap_err();
+            assert_eq!(err.day, solver.day());
+        }
+    }
+}
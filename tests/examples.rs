@@ -0,0 +1,30 @@
+//! Worked examples from the puzzle descriptions, stored as `tests/examples/dayN.txt`.
+//! Their answers are the `examples` set of `answers.toml`.
+use aoc_2021::answers::parse_answers;
+use aoc_2021::DAYS;
+use std::fs;
+
+fn solve_example(day: u8, part: u8) -> String {
+    let input = fs::read_to_string(format!("tests/examples/day{}.txt", day)).unwrap();
+    DAYS[day as usize - 1].solve(&input, part).unwrap().unwrap()
+}
+
+#[test]
+fn test_examples() {
+    let sets = parse_answers(&fs::read_to_string("answers.toml").unwrap()).unwrap();
+    let examples = sets.iter().find(|set| set.name == "examples").unwrap();
+
+    for day in 1..=DAYS.len() as u8 {
+        for part in [1, 2] {
+            if let Some(answer) = examples.expected(day, part) {
+                assert_eq!(
+                    solve_example(day, part),
+                    answer,
+                    "day {} part {}",
+                    day,
+                    part
+                );
+            }
+        }
+    }
+}